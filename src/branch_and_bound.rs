@@ -0,0 +1,366 @@
+use std::fmt::{Debug, Display};
+
+use num::{
+    traits::NumAssign, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, One, Rational64,
+    Signed, Zero,
+};
+
+use crate::{
+    errors::SimplexMethodError,
+    parser::{Goal, Method, Relation, Restriction, TargetFn, Task, Term},
+    simplex::{OutputStyle, SimplexSolver, Solution},
+    solve, solve_task,
+    task::{SimplexTask, Taxes},
+    tax_numbers::{LiftRational, Tax},
+};
+
+/// Solves `task` with the variables `integers` restricted to integer values. The LP relaxation
+/// of every subproblem is solved by taxes, which tell an infeasible one apart. A relaxation
+/// with a fractional integer variable `x = v` branches into `x <= floor(v)` and
+/// `x >= ceil(v)`, and subproblems whose relaxation is no better than the best integer solution
+/// found so far are pruned.
+///
+/// Returns `Ok(None)` if there is no integer feasible point.
+pub fn branch_and_bound(
+    task: SimplexTask<Tax<Rational64>>,
+    integers: &[u64],
+) -> Result<Option<Solution<Tax<Rational64>>>, SimplexMethodError> {
+    let goal = task.goal().clone();
+    let better = |x: &Tax<Rational64>, y: &Tax<Rational64>| match goal {
+        Goal::Maximize => x > y,
+        Goal::Minimize => x < y,
+    };
+
+    let mut best: Option<Solution<Tax<Rational64>>> = None;
+    let mut stack = vec![task];
+    while let Some(task) = stack.pop() {
+        let solver: SimplexSolver<_> = task.clone().canonize::<Taxes>().into();
+        let solution = match solver.solve() {
+            Ok(solution) => solution,
+            Err(SimplexMethodError::Infeasible { .. }) => continue,
+            Err(err) => return Err(err),
+        };
+        let objective = solution.objective_value();
+        if let Some(best) = &best {
+            if !better(&objective, &best.objective_value()) {
+                continue;
+            }
+        }
+
+        let values = solution.variable_values();
+        let fractional = integers.iter().find_map(|&index| {
+            let value = values.get(&(index as usize))?.real()?;
+            (!value.is_integer()).then_some((index, value))
+        });
+        match fractional {
+            Some((index, value)) => {
+                for (relation, limit) in [
+                    (Relation::Less, value.floor()),
+                    (Relation::Greater, value.ceil()),
+                ] {
+                    let mut branch = task.clone();
+                    branch.bound_variable(index, relation, limit.into());
+                    stack.push(branch);
+                }
+            }
+            None => best = Some(solution),
+        }
+    }
+
+    Ok(best)
+}
+
+/// Looks for any point satisfying the restrictions of `task`. The objective is dropped and
+/// taxes drive the artificial variables out of the basis, so the task is reported infeasible if
+/// there is no such point.
+pub fn feasible_point<N>(mut task: Task) -> Result<Option<Vec<Tax<N>>>, SimplexMethodError>
+where
+    N: Display + Debug + Num + NumAssign + Ord + Clone,
+    N: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv,
+    Tax<N>: LiftRational,
+{
+    let variables = task
+        .restrictions
+        .iter()
+        .flat_map(|x| &x.terms)
+        .map(|x| x.index as usize)
+        .max()
+        .unwrap_or(0);
+    task.target_fn = TargetFn {
+        goal: Goal::Minimize,
+        terms: vec![],
+        value: Rational64::zero(),
+    };
+    task.method = Method::Taxes;
+
+    match solve::<N>(task, OutputStyle::SolutionOnly, None) {
+        Ok(solution) => Ok(Some(solution.point(variables))),
+        Err(SimplexMethodError::Infeasible { .. }) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// A rounding heuristic for integer variables, not an exact method: solves the relaxation, then
+/// pins `integers` one by one to their rounded value, or to the floor or the ceiling if that
+/// leaves no feasible point, solving again after each. The result is integer feasible but may be
+/// worse than the integer optimum, and `Ok(None)` means that rounding ran into infeasibility.
+pub fn solve_integer_rounding(
+    mut task: Task,
+    integers: &[u64],
+) -> Result<Option<Solution<Tax<Rational64>>>, SimplexMethodError> {
+    // unlike the other methods, taxes tell an infeasible task apart
+    task.method = Method::Taxes;
+    let mut solution = match solve_task(task.clone()) {
+        Ok(solution) => solution,
+        Err(SimplexMethodError::Infeasible { .. }) => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    for &index in integers {
+        let Some(value) = solution.point(index as usize)[index as usize - 1].real() else {
+            return Ok(None);
+        };
+        let mut candidates = vec![value.round(), value.floor(), value.ceil()];
+        candidates.dedup();
+
+        let mut pinned = None;
+        for candidate in candidates {
+            let mut fixed = task.clone();
+            fixed.fix_variable(index, candidate);
+            match solve_task(fixed.clone()) {
+                Ok(solution) => {
+                    pinned = Some((fixed, solution));
+                    break;
+                }
+                Err(
+                    SimplexMethodError::Unbounded
+                    | SimplexMethodError::Infeasible { .. }
+                    | SimplexMethodError::NoSolutions,
+                ) => (),
+                Err(err) => return Err(err),
+            }
+        }
+        let Some((fixed, fixed_solution)) = pinned else {
+            return Ok(None);
+        };
+        (task, solution) = (fixed, fixed_solution);
+    }
+
+    Ok(Some(solution))
+}
+
+/// How many rounding rounds [`feasibility_pump`] makes before giving up
+pub const PUMP_ITERATIONS: usize = 50;
+
+/// A feasibility pump for integer variables, looking for an integer feasible point with no
+/// regard for the objective. The relaxation point is rounded, and while the rounding violates
+/// the restrictions the nearest relaxation point to it in `L1` distance becomes the next point.
+/// When the rounding stops changing, the most fractional variable is flipped to its other
+/// neighbour to leave the cycle.
+///
+/// Returns the point with the objective value at it, or `Ok(None)` when the task is infeasible
+/// or no point was found in `max_iterations` rounds.
+pub fn feasibility_pump(
+    task: Task,
+    integers: &[u64],
+    max_iterations: usize,
+) -> Result<Option<(Vec<Rational64>, Rational64)>, SimplexMethodError> {
+    let variables = task.next_index() as usize - 1;
+    // the rows the solver sees, bounds included
+    let rows: SimplexTask<Rational64> = task.clone().into();
+    let relaxed = |mut task: Task| -> Result<Option<Vec<Rational64>>, SimplexMethodError> {
+        task.method = Method::Taxes;
+        match solve_task(task) {
+            Ok(solution) => {
+                let values = solution.variable_values();
+                Ok((1..=variables)
+                    .map(|i| values.get(&i).cloned().unwrap_or_else(Tax::zero))
+                    .map(Tax::real)
+                    .collect())
+            }
+            Err(SimplexMethodError::Infeasible { .. }) => Ok(None),
+            Err(err) => Err(err),
+        }
+    };
+    let objective = |point: &[Rational64]| {
+        task.target_fn
+            .terms
+            .iter()
+            .map(|x| x.coef * point[x.index as usize - 1])
+            .sum::<Rational64>()
+            + task.target_fn.value
+    };
+
+    let Some(mut point) = relaxed(task.clone())? else {
+        return Ok(None);
+    };
+    let mut previous: Option<Vec<Rational64>> = None;
+    for _ in 0..max_iterations {
+        let mut rounded = point.clone();
+        for &index in integers {
+            let x = &mut rounded[index as usize - 1];
+            *x = x.round();
+        }
+        if previous.as_ref() == Some(&rounded) {
+            let flipped = integers
+                .iter()
+                .map(|&index| index as usize - 1)
+                .max_by_key(|&i| (point[i] - rounded[i]).abs());
+            if let Some(i) = flipped {
+                let step = if point[i] < rounded[i] { -1 } else { 1 };
+                rounded[i] += step;
+            }
+        }
+        if rows.holds_at(&rounded) {
+            let z = objective(&rounded);
+            return Ok(Some((rounded, z)));
+        }
+
+        // |x - r| is bounded from below by an extra variable `d` through `d - x >= -r` and
+        // `d + x >= r`, so minimizing the sum of those measures the distance to the rounding
+        let mut distance = task.clone();
+        distance.target_fn = TargetFn {
+            goal: Goal::Minimize,
+            terms: vec![],
+            value: Rational64::zero(),
+        };
+        for (&index, d) in integers.iter().zip(variables as u64 + 1..) {
+            let target = rounded[index as usize - 1];
+            for sign in [-1, 1] {
+                distance.restrictions.push(Restriction {
+                    relation: Relation::Greater,
+                    terms: vec![
+                        Term {
+                            coef: Rational64::one(),
+                            index: d,
+                        },
+                        Term {
+                            coef: sign.into(),
+                            index,
+                        },
+                    ],
+                    value: target * sign,
+                    section: None,
+                });
+            }
+            distance.target_fn.terms.push(Term {
+                coef: Rational64::one(),
+                index: d,
+            });
+        }
+
+        let Some(next) = relaxed(distance)? else {
+            return Ok(None);
+        };
+        (point, previous) = (next, Some(rounded));
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Rational64;
+
+    use crate::{
+        branch_and_bound::{
+            branch_and_bound, feasibility_pump, feasible_point, solve_integer_rounding,
+            PUMP_ITERATIONS,
+        },
+        parser::Task,
+        task::SimplexTask,
+        tax_numbers::Tax,
+    };
+
+    #[test]
+    fn test_knapsack() {
+        let task: Task = "5x1 + 7x2 + 4x3 + 3x4 <= 14\nx1 <= 1\nx2 <= 1\nx3 <= 1\nx4 <= 1\n\
+                          z = 8x1 + 11x2 + 6x3 + 4x4 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+
+        let solution = branch_and_bound(task, &[1, 2, 3, 4]).unwrap().unwrap();
+
+        // the relaxation takes half of x3 for 22, the integer optimum leaves x1 out instead
+        let value = |x: i64| Tax::from(Rational64::from(x));
+        assert_eq!(solution.objective_value(), value(21));
+        assert_eq!(solution.point(4), [value(0), value(1), value(1), value(1)]);
+    }
+
+    #[test]
+    fn test_no_integer_point() {
+        let task: Task = "2x1 >= 1\n2x1 <= 1\nz = x1 -> max".parse().unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+
+        // x1 is held at 1/2
+        assert!(matches!(branch_and_bound(task, &[1]), Ok(None)));
+    }
+
+    #[test]
+    fn test_integer_rounding() {
+        // the relaxation is at (3, 3/2) with z = 21, the integer optimum is at (4, 0) with z = 20
+        let task: Task = "6x1 + 4x2 <= 24\nx1 + 2x2 <= 6\nz = 5x1 + 4x2 -> max"
+            .parse()
+            .unwrap();
+        let optimum = branch_and_bound(task.clone().into(), &[1, 2])
+            .unwrap()
+            .unwrap()
+            .objective_rational()
+            .unwrap();
+
+        let solution = solve_integer_rounding(task, &[1, 2]).unwrap().unwrap();
+        let point = solution
+            .point(2)
+            .into_iter()
+            .map(|x| x.real().unwrap())
+            .collect::<Vec<_>>();
+        let z = solution.objective_rational().unwrap();
+
+        assert!(point.iter().all(|x| x.is_integer()));
+        let [x1, x2] = [point[0].to_integer(), point[1].to_integer()];
+        assert!(6 * x1 + 4 * x2 <= 24 && x1 + 2 * x2 <= 6);
+        assert_eq!(z, (5 * x1 + 4 * x2).into());
+        assert!(z <= optimum);
+    }
+
+    #[test]
+    fn test_feasibility_pump() {
+        // plain rounding of the relaxation at (3, 3/2) gives the infeasible (3, 2)
+        let task: Task = "6x1 + 4x2 <= 24\nx1 + 2x2 <= 6\nz = 5x1 + 4x2 -> max"
+            .parse()
+            .unwrap();
+
+        let (point, z) = feasibility_pump(task.clone(), &[1, 2], PUMP_ITERATIONS)
+            .unwrap()
+            .unwrap();
+
+        assert!(point.iter().all(|x| x.is_integer()));
+        assert!(task.restrictions.iter().all(|x| x.holds_at(&point)));
+        assert_eq!(z, point[0] * 5 + point[1] * 4);
+    }
+
+    #[test]
+    fn test_feasibility_pump_bounds() {
+        // the relaxation is at x1 = 5/2, rounded up past the bound but within the restriction
+        let task: Task = "x1 + x2 <= 10\nz = x1 -> max\nx1 <= 5/2".parse().unwrap();
+
+        let (point, _) = feasibility_pump(task, &[1], PUMP_ITERATIONS)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(point[0], 2.into());
+    }
+
+    #[test]
+    fn test_feasibility() {
+        let feasible: Task = "x1 + x2 >= 3\nx1 <= 1\nx2 <= 4".parse().unwrap();
+        let infeasible: Task = "x1 + x2 >= 3\nx1 <= 1\nx2 <= 1".parse().unwrap();
+
+        let point = feasible_point::<Rational64>(feasible).unwrap().unwrap();
+        let point = point.into_iter().map(Tax::real).collect::<Vec<_>>();
+        assert!(point[0].unwrap() + point[1].unwrap() >= 3.into());
+        assert!(point[0].unwrap() <= 1.into() && point[1].unwrap() <= 4.into());
+        assert!(matches!(feasible_point::<Rational64>(infeasible), Ok(None)));
+    }
+}
@@ -0,0 +1,202 @@
+use num::{Rational64, Zero};
+
+use crate::parser::{BoundKind, Goal, Method, Relation, Restriction, TargetFn, Task, Term};
+
+impl Task {
+    /// Builds the dual problem with one nonnegative variable per restriction.
+    ///
+    /// Restrictions are first turned into `<=` for maximization or `>=` for minimization, so that
+    /// every dual variable keeps the sign the input format supports. An equality has a free dual
+    /// value, so it is split into a `<=` and `>=` pair and contributes two dual variables.
+    ///
+    /// Bounds other than `x >= 0` take part as restrictions of their own. A variable that may be
+    /// negative, being free or bounded from below by a negative number, gets an equality in the
+    /// dual instead of an inequality.
+    pub fn dual(&self) -> Task {
+        let mut primal = self.restrictions.clone();
+        let mut free = vec![];
+        for bound in &self.bounds {
+            let (relation, value) = match bound.kind {
+                BoundKind::Free => {
+                    free.push(bound.index);
+                    continue;
+                }
+                BoundKind::Upper(limit) => (Relation::Less, limit),
+                BoundKind::Lower(limit) if limit.is_zero() => continue,
+                BoundKind::Lower(limit) => {
+                    if limit < Rational64::zero() {
+                        free.push(bound.index);
+                    }
+                    (Relation::Greater, limit)
+                }
+            };
+            primal.push(Restriction {
+                relation,
+                terms: vec![Term {
+                    coef: 1.into(),
+                    index: bound.index,
+                }],
+                value,
+                section: None,
+            });
+        }
+
+        let mut rows = vec![];
+        for restriction in &primal {
+            match restriction.relation {
+                Relation::Equal => {
+                    rows.push((&restriction.terms, restriction.value, Relation::Less));
+                    rows.push((&restriction.terms, restriction.value, Relation::Greater));
+                }
+                ref relation => {
+                    rows.push((&restriction.terms, restriction.value, relation.clone()))
+                }
+            }
+        }
+        let (expected, goal, relation) = match self.target_fn.goal {
+            Goal::Maximize => (Relation::Less, Goal::Minimize, Relation::Greater),
+            Goal::Minimize => (Relation::Greater, Goal::Maximize, Relation::Less),
+        };
+        let sign = |relation: &Relation| {
+            if *relation == expected {
+                Rational64::from(1)
+            } else {
+                Rational64::from(-1)
+            }
+        };
+
+        let mut variables = primal
+            .iter()
+            .flat_map(|x| &x.terms)
+            .chain(&self.target_fn.terms)
+            .map(|x| x.index)
+            .collect::<Vec<_>>();
+        variables.sort();
+        variables.dedup();
+
+        let restrictions = variables
+            .into_iter()
+            .map(|index| Restriction {
+                relation: if free.contains(&index) {
+                    Relation::Equal
+                } else {
+                    relation.clone()
+                },
+                terms: rows
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, (terms, _, relation))| {
+                        let coef = terms
+                            .iter()
+                            .filter(|x| x.index == index)
+                            .map(|x| x.coef)
+                            .sum::<Rational64>();
+
+                        (!coef.is_zero()).then(|| Term {
+                            coef: coef * sign(relation),
+                            index: i as u64 + 1,
+                        })
+                    })
+                    .collect(),
+                value: self
+                    .target_fn
+                    .terms
+                    .iter()
+                    .filter(|x| x.index == index)
+                    .map(|x| x.coef)
+                    .sum(),
+                section: None,
+            })
+            // a variable no restriction limits leaves `0 >= c` for the dual, which only goes
+            // without saying if it holds
+            .filter(|x| !x.terms.is_empty() || !x.holds_at(&[]))
+            .collect();
+
+        Task {
+            restrictions,
+            target_fn: TargetFn {
+                goal,
+                terms: rows
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, value, relation))| Term {
+                        coef: value * sign(relation),
+                        index: i as u64 + 1,
+                    })
+                    .collect(),
+                value: self.target_fn.value,
+            },
+            bounds: vec![],
+            method: Method::Taxes,
+        }
+    }
+
+    /// Renders [`Task::dual`] in the input format with dual variables named `y1, y2, ...`
+    pub fn dual_to_dsl(&self) -> String {
+        self.dual().render('y')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Rational64;
+
+    use crate::{errors::SimplexMethodError, parser::Task, simplex::OutputStyle, solve};
+
+    const PRIMAL: &str = "x1 <= 4
+2x2 <= 12
+3x1 + 2x2 <= 18
+z = 3x1 + 5x2 -> max";
+
+    #[test]
+    fn test_dual_variables() {
+        let task: Task = PRIMAL.parse().unwrap();
+        let dual = task.dual_to_dsl();
+
+        assert_eq!(
+            dual,
+            "y1 + 3y3 >= 3\n2y2 + 2y3 >= 5\nz = 4y1 + 12y2 + 18y3 -> min\nsolve using taxes\n"
+        );
+        assert_eq!(task.dual().target_fn.terms.len(), task.restrictions.len());
+    }
+
+    #[test]
+    fn test_bounded_duality() {
+        let optimum = |task: Task| {
+            let solution = solve::<Rational64>(task, OutputStyle::SolutionOnly, None).unwrap();
+            solution.objective_rational().unwrap()
+        };
+
+        for input in [
+            "x1 + x2 <= 2\nx2 <= 5\nz = x2 -> max\nx1 >= -3",
+            "x1 + x2 <= 2\nx2 <= 5\nz = x2 - x1 -> max\nx1 free\nx1 >= -6",
+            "x1 + x2 <= 10\nz = 2x1 + x2 -> max\nx1 <= 3",
+            "x1 + x2 >= 2\nz = x1 + 3x2 -> min\nx1 <= 1/2",
+        ] {
+            let task: Task = format!("{input}\nsolve using taxes").parse().unwrap();
+            assert_eq!(optimum(task.dual()), optimum(task), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_unbounded_primal() {
+        // nothing limits x2, so the dual has to make `0 >= 1` hold
+        let task: Task = "x1 <= 4\nz = x1 + x2 -> max".parse().unwrap();
+
+        assert!(task.dual_to_dsl().contains("\n0 >= 1\n"));
+        assert!(matches!(
+            solve::<Rational64>(task.dual(), OutputStyle::SolutionOnly, None),
+            Err(SimplexMethodError::Infeasible { .. })
+        ));
+    }
+
+    #[test]
+    fn test_strong_duality() {
+        let task: Task = PRIMAL.parse().unwrap();
+        let primal = solve::<Rational64>(task.clone(), OutputStyle::SolutionOnly, None).unwrap();
+        let dual = solve::<Rational64>(task.dual(), OutputStyle::SolutionOnly, None).unwrap();
+
+        assert!(primal.to_string().starts_with("Optimal z is: 36\n"));
+        assert!(dual.to_string().starts_with("Optimal z is: 36\n"));
+    }
+}
@@ -1,8 +1,148 @@
-use std::fmt::Debug;
+use std::fmt::{Debug, Display};
 
-#[allow(dead_code)]
 #[derive(Debug)]
 pub enum SimplexMethodError {
-    NoLimit,
+    /// An improving column has no positive entry, so the objective grows without bound
+    Unbounded,
+    /// Artificial variables are left positive at the optimum of taxes, adding up to `penalty`
+    Infeasible {
+        penalty: String,
+    },
     NoSolutions,
+    /// Rational arithmetic left the range of the underlying integers
+    Overflow,
+    /// Solving took longer than the time limit given to the solver
+    TimeLimitExceeded,
+    /// The solver made this many pivots without reaching the optimum, which usually means it
+    /// cycles on a degenerate task
+    IterationLimitExceeded(usize),
+    /// The task has no restrictions to build the tableau from
+    NoRestrictions,
+}
+
+impl Display for SimplexMethodError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimplexMethodError::Unbounded => write!(f, "target function is unbounded"),
+            SimplexMethodError::Infeasible { penalty } => write!(
+                f,
+                "the task is infeasible, artificial variables keep a penalty of {penalty}M"
+            ),
+            SimplexMethodError::NoSolutions => write!(f, "no pivot column could be chosen"),
+            SimplexMethodError::Overflow => write!(
+                f,
+                "numeric overflow during pivoting, try solving with the --bignum backend"
+            ),
+            SimplexMethodError::TimeLimitExceeded => write!(f, "time limit exceeded"),
+            SimplexMethodError::IterationLimitExceeded(iterations) => write!(
+                f,
+                "no optimum after {iterations} iterations, try Bland's rule against cycling"
+            ),
+            SimplexMethodError::NoRestrictions => write!(f, "the task has no restrictions"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SimplexBuildError {
+    /// A variable index so far beyond the number of terms that the tableau would be mostly empty
+    IndexTooLarge { index: u64, limit: u64 },
+    /// The tableau has no restriction rows besides the objective row
+    NoRestrictions,
+    /// The tableau has no variable columns besides the free term
+    NoVariables,
+    /// A warm-start basis does not have a column for every restriction row
+    BasisSize { expected: usize, found: usize },
+    /// A warm-start basis is singular, out of range or infeasible for the tableau
+    InvalidBasis,
+    /// A warm-start basis file could not be read
+    BasisFile(String),
+    /// Indices below the largest one which no variable uses
+    MissingIndices(Vec<u64>),
+}
+
+impl Display for SimplexBuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SimplexBuildError::IndexTooLarge { index, limit } => write!(
+                f,
+                "variable x{index} exceeds the largest accepted index {limit} for this task"
+            ),
+            SimplexBuildError::NoRestrictions => write!(f, "the tableau has no restrictions"),
+            SimplexBuildError::NoVariables => write!(f, "the tableau has no variables"),
+            SimplexBuildError::BasisSize { expected, found } => write!(
+                f,
+                "the basis has {found} columns, but the tableau has {expected} restrictions"
+            ),
+            SimplexBuildError::InvalidBasis => {
+                write!(f, "the basis is singular or infeasible for this tableau")
+            }
+            SimplexBuildError::BasisFile(err) => write!(f, "cannot read the basis: {err}"),
+            SimplexBuildError::MissingIndices(missing) => {
+                let labels: Vec<_> = missing.iter().map(|x| format!("x{x}")).collect();
+                write!(f, "the task never uses {}", labels.join(", "))
+            }
+        }
+    }
+}
+
+/// Input that is not a task, with the place parsing stopped at
+#[derive(Debug, PartialEq)]
+pub struct SimplexParseErr {
+    /// What the parser looked for there, like `relation`
+    pub expected: String,
+    /// Line of the error, counted from 1
+    pub line: usize,
+    /// Character of the error within its line, counted from 1
+    pub column: usize,
+}
+
+impl Display for SimplexParseErr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "expected {} at line {}, column {}",
+            self.expected, self.line, self.column
+        )
+    }
+}
+
+/// A solved tableau that breaks the optimality conditions, which means a bug in the solver
+#[derive(Debug, PartialEq)]
+pub enum OptimalityViolation {
+    /// The starting basis was not an identity matrix, so there are no dual prices to check
+    NoDuals,
+    /// The basic variable of the row is negative
+    PrimalInfeasible { row: usize },
+    /// The reduced cost of the column still improves the objective
+    DualInfeasible { column: usize },
+    /// The basic column has a nonzero reduced cost
+    ComplementarySlackness { column: usize },
+    /// The objective and the dual objective differ by `discrepancy`
+    DualityGap { discrepancy: String },
+}
+
+impl Display for OptimalityViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptimalityViolation::NoDuals => {
+                write!(f, "the starting basis gives no dual prices to check")
+            }
+            OptimalityViolation::PrimalInfeasible { row } => {
+                write!(f, "the basic variable of row {row} is negative")
+            }
+            OptimalityViolation::DualInfeasible { column } => write!(
+                f,
+                "x{} still improves the objective at the optimum",
+                column + 1
+            ),
+            OptimalityViolation::ComplementarySlackness { column } => {
+                write!(f, "x{} is basic but has a nonzero reduced cost", column + 1)
+            }
+            OptimalityViolation::DualityGap { discrepancy } => write!(
+                f,
+                "the objective differs from the dual objective by {discrepancy}"
+            ),
+        }
+    }
 }
@@ -0,0 +1,69 @@
+//! The simplex method and its variations: the input format, canonization of tasks and the
+//! solver. [`solve_task`] runs the whole pipeline for a parsed task.
+
+use std::{
+    fmt::{Debug, Display},
+    time::Duration,
+};
+
+use num::{traits::NumAssign, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, Rational64};
+
+use crate::{
+    errors::SimplexMethodError,
+    parser::{Method, Task},
+    simplex::{OutputStyle, SimplexSolver, Solution},
+    task::{DoublePhase, Simple, SimplexTask, Taxes},
+    tax_numbers::{LiftRational, Tax},
+};
+
+pub mod branch_and_bound;
+pub mod dual;
+pub mod errors;
+pub mod parser;
+pub mod simplex;
+pub mod task;
+pub mod tax_numbers;
+
+/// Canonizes `task` by its method and solves it in `Tax<N>` numbers
+pub fn solve<N>(
+    task: Task,
+    style: OutputStyle,
+    time_limit: Option<Duration>,
+) -> Result<Solution<Tax<N>>, SimplexMethodError>
+where
+    N: Display + Debug + Num + NumAssign + Ord + Clone,
+    N: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv,
+    Tax<N>: LiftRational,
+{
+    let method = task.method;
+    let task: SimplexTask<Tax<N>> = task.into();
+    let solver: SimplexSolver<Tax<N>> = match method {
+        Method::Simple => task.canonize::<Simple>().into(),
+        Method::Taxes => task.canonize::<Taxes>().into(),
+        Method::SecondPhase => task.canonize::<DoublePhase>().into(),
+    };
+
+    solver.with_style(style).with_time_limit(time_limit).solve()
+}
+
+/// Solves a parsed task by its method in rational numbers, printing nothing
+pub fn solve_task(task: Task) -> Result<Solution<Tax<Rational64>>, SimplexMethodError> {
+    solve(task, OutputStyle::SolutionOnly, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Rational64;
+
+    use crate::{parser::Task, solve_task, tax_numbers::Tax};
+
+    #[test]
+    fn test_solve_task() {
+        let task: Task = "x1 + x2 <= 4\nx1 <= 3\nz = 2x1 + x2 -> max"
+            .parse()
+            .unwrap();
+        let solution = solve_task(task).unwrap();
+
+        assert_eq!(solution.objective_value(), Tax::from(Rational64::from(7)));
+    }
+}
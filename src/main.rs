@@ -1,35 +1,519 @@
-#![feature(slice_flatten)]
-
-use std::{env::args, fs::read_to_string};
-
-use num::Rational64;
+use std::{
+    env::args,
+    fmt::{Debug, Display},
+    fs::{read, write},
+    io::{self, stdin, stdout, BufRead, Write},
+    process::exit,
+    time::Duration,
+};
 
-use crate::{
-    parser::Task,
-    simplex::SimplexSolver,
-    task::{DoublePhase, Simple, SimplexTask, Taxes},
-    tax_numbers::Tax,
+use num::{
+    traits::NumAssign, BigRational, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, Rational64,
 };
 
-mod errors;
-mod parser;
-mod simplex;
-mod task;
-mod tax_numbers;
+use simplex::{
+    branch_and_bound::{feasibility_pump, feasible_point, solve_integer_rounding, PUMP_ITERATIONS},
+    errors::{OptimalityViolation, SimplexMethodError},
+    parser::{dimacs::Network, ParserConfig, Restriction, Task, DEFAULT_INDEX_RATIO},
+    simplex::{OutputFormat, OutputStyle, Solution},
+    solve,
+    task::SimplexTask,
+    tax_numbers::{LiftRational, Tax},
+};
 
 fn main() {
-    let input_path = args().nth(1).unwrap_or("input.txt".to_owned());
-    let input = read_to_string(input_path).unwrap();
-
-    let task: Task = input.parse().expect("Cannot parse given input");
-    let method = task.method;
-    let task: SimplexTask<Tax<Rational64>> = task.into();
-    let solver: SimplexSolver<Tax<Rational64>> = match method {
-        parser::Method::Simple => task.canonize::<Simple>().into(),
-        parser::Method::Taxes => task.canonize::<Taxes>().into(),
-        parser::Method::SecondPhase => task.canonize::<DoublePhase>().into(),
+    let mut args = args().skip(1);
+    let mut input_path = "input.txt".to_owned();
+    let (mut bignum, mut dimacs, mut interactive, mut dual) = (false, false, false, false);
+    let (mut feasibility, mut presolve, mut pump) = (false, false, false);
+    let (mut log_objective, mut validate_duals, mut describe) = (false, false, false);
+    let mut contiguous = false;
+    let mut pivot_log = None;
+    let mut time_limit = None;
+    let mut config = ParserConfig::default();
+    let mut integers = vec![];
+    let mut style = OutputStyle::default();
+    let mut format = OutputFormat::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bignum" => bignum = true,
+            "--dimacs" => dimacs = true,
+            "--repl" => interactive = true,
+            "--dual" => dual = true,
+            "--feasibility" => feasibility = true,
+            "--presolve" => presolve = true,
+            "--pump" => pump = true,
+            "--log-objective" => log_objective = true,
+            "--validate-duals" => validate_duals = true,
+            "--describe" => describe = true,
+            "--contiguous" => contiguous = true,
+            "--pivot-log" => match args.next() {
+                Some(path) => pivot_log = Some(path),
+                None => {
+                    eprintln!("Cannot read --pivot-log: expected a file name");
+                    exit(2);
+                }
+            },
+            "--pretty" => style = OutputStyle::Pretty,
+            "--max-time" => {
+                let seconds = args.next().unwrap_or_default().parse();
+                time_limit = match seconds.map(Duration::try_from_secs_f64) {
+                    Ok(Ok(limit)) => Some(limit),
+                    _ => {
+                        eprintln!("Cannot read --max-time: expected a number of seconds");
+                        exit(2);
+                    }
+                }
+            }
+            "--index-base" => {
+                config.index_base = match args.next().unwrap_or_default().parse() {
+                    Ok(base) => base,
+                    Err(_) => {
+                        eprintln!("Cannot read --index-base: expected a number like 0 or 1");
+                        exit(2);
+                    }
+                }
+            }
+            "--integer" => {
+                let list = args.next().unwrap_or_default();
+                integers = match list
+                    .split(',')
+                    .map(|x| x.trim().strip_prefix('x')?.parse().ok())
+                    .collect()
+                {
+                    Some(integers) => integers,
+                    None => {
+                        eprintln!("Cannot read --integer: expected variables like x1,x3");
+                        exit(2);
+                    }
+                }
+            }
+            "--format" => {
+                format = match args.next().unwrap_or_default().parse() {
+                    Ok(format) => format,
+                    Err(err) => {
+                        eprintln!("Cannot read --format: {err}");
+                        exit(2);
+                    }
+                }
+            }
+            "--style" => {
+                style = match args.next().unwrap_or_default().parse() {
+                    Ok(style) => style,
+                    Err(err) => {
+                        eprintln!("Cannot read --style: {err}");
+                        exit(2);
+                    }
+                }
+            }
+            _ if arg.starts_with("--") => {
+                eprintln!("Unknown option {arg}");
+                exit(2);
+            }
+            _ => input_path = arg,
+        }
+    }
+    let input = read_input(&input_path).unwrap();
+
+    let task: Task = if dimacs {
+        let network: Network = input.parse().expect("Cannot parse given network");
+        for (i, (from, to)) in network.arcs.iter().enumerate() {
+            println!("x{} is the flow along {from} -> {to}", i + 1);
+        }
+        network.task
+    } else {
+        match Task::parse_with(&input, config) {
+            Ok(task) => task,
+            Err(err) => {
+                eprintln!("Cannot parse given input: {err}");
+                exit(1);
+            }
+        }
+    };
+    let base = config.index_base;
+    let integers = match integer_indices(&integers, base, task.next_index() - 1) {
+        Ok(integers) => integers,
+        Err(err) => {
+            eprintln!("Cannot read --integer: {err}");
+            exit(2);
+        }
     };
-    let solution = solver.solve().expect("Cannot get solution");
+    let index_base = base as usize;
+    if let Err(err) = task.check_indices(DEFAULT_INDEX_RATIO) {
+        eprintln!("Cannot build the task: {err}");
+        exit(1);
+    }
+    if contiguous {
+        if let Err(err) = task.check_contiguous(base) {
+            eprintln!("Cannot build the task: {err}");
+            exit(1);
+        }
+    }
+    let task = if dual {
+        print!("{}", task.dual_to_dsl());
+        task.dual()
+    } else {
+        task
+    };
+    if describe {
+        let method = task.method;
+        let task: SimplexTask<Rational64> = task.into();
+        print!("{}", task.describe(method));
+    } else if interactive {
+        repl(task, stdin().lock(), stdout()).expect("Cannot run the repl");
+    } else if feasibility {
+        match feasible_point::<BigRational>(task) {
+            Ok(Some(point)) => {
+                println!("feasible");
+                for (i, x) in point.iter().enumerate() {
+                    println!("   x{} = {x}", i + index_base);
+                }
+            }
+            Ok(None) => println!("infeasible"),
+            Err(err) => {
+                eprintln!("Cannot check feasibility: {err}");
+                exit(1);
+            }
+        }
+    } else {
+        let mut task = task;
+        let fixed = if presolve {
+            let fixed = task.substitute_singletons();
+            println!("Presolve eliminated {} variables", fixed.len());
+            fixed
+        } else {
+            vec![]
+        };
+
+        if !integers.is_empty() && pump {
+            match feasibility_pump(task, &integers, PUMP_ITERATIONS) {
+                Ok(Some((point, z))) => {
+                    let values = point
+                        .iter()
+                        .enumerate()
+                        .map(|(i, x)| format!("x{} = {x}", i + index_base))
+                        .collect::<Vec<_>>();
+                    println!("Feasibility pump found z = {z} at {}", values.join(", "));
+                }
+                Ok(None) => println!("Feasibility pump found no integer feasible point"),
+                Err(err) => report::<Tax<Rational64>>(Err(err), style, format),
+            }
+        } else if !integers.is_empty() {
+            match solve_integer_rounding(task, &integers) {
+                Ok(Some(solution)) => report(
+                    Ok(solution
+                        .with_fixed(columns(&fixed))
+                        .with_index_base(index_base)),
+                    style,
+                    format,
+                ),
+                Ok(None) => println!("Rounding found no integer feasible point"),
+                Err(err) => report::<Tax<Rational64>>(Err(err), style, format),
+            }
+        } else {
+            let options = RunOptions {
+                style,
+                format,
+                time_limit,
+                log_objective,
+                validate_duals,
+                pivot_log: pivot_log.as_deref(),
+                fixed: &fixed,
+                index_base,
+            };
+            if bignum {
+                run::<BigRational>(task, &options);
+            } else {
+                run::<Rational64>(task, &options);
+            }
+        }
+    }
+}
+
+/// What to do with a solution besides reporting it, as the options asked
+struct RunOptions<'a> {
+    style: OutputStyle,
+    format: OutputFormat,
+    time_limit: Option<Duration>,
+    log_objective: bool,
+    validate_duals: bool,
+    pivot_log: Option<&'a str>,
+    fixed: &'a [(u64, Rational64)],
+    index_base: usize,
+}
+
+/// Solves `task` in the numbers `N` and reports the solution
+fn run<N>(task: Task, options: &RunOptions)
+where
+    N: Display + Debug + Num + NumAssign + Ord + Clone,
+    N: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv,
+    Tax<N>: LiftRational,
+{
+    let result = solve::<N>(task, options.style, options.time_limit);
+    if options.log_objective {
+        print_objectives(&result);
+    }
+    if options.validate_duals {
+        check_optimality(&result);
+    }
+    if let Some(path) = options.pivot_log {
+        write_pivot_log(&result, path);
+    }
+    let result = result.map(|x| {
+        x.with_fixed(columns(options.fixed))
+            .with_index_base(options.index_base)
+    });
+    report(result, options.style, options.format);
+}
+
+/// Turns the `--integer` variables, written from the index `base`, into the solver's indices
+/// counted from 1, rejecting any beyond the `variables` the task has
+fn integer_indices(integers: &[u64], base: u64, variables: u64) -> Result<Vec<u64>, String> {
+    integers
+        .iter()
+        .map(|&x| match x.saturating_add(1).checked_sub(base) {
+            Some(index) if (1..=variables).contains(&index) => Ok(index),
+            _ => Err(format!("the task has no variable x{x}")),
+        })
+        .collect()
+}
+
+/// Turns `(x{index}, value)` pairs into `(column, value)` ones in the solver's numbers
+fn columns<N: LiftRational>(values: &[(u64, Rational64)]) -> Vec<(usize, N)> {
+    values
+        .iter()
+        .map(|(index, value)| (*index as usize - 1, N::lift(*value)))
+        .collect()
+}
+
+/// Reads the input file, decompressing it first if it has the `.gz` extension or the gzip magic
+/// bytes
+fn read_input(path: &str) -> io::Result<String> {
+    let bytes = read(path)?;
+    #[cfg(feature = "gzip")]
+    if path.ends_with(".gz") || bytes.starts_with(&[0x1f, 0x8b]) {
+        use std::io::Read;
+
+        let mut input = String::new();
+        flate2::read::GzDecoder::new(bytes.as_slice()).read_to_string(&mut input)?;
+        return Ok(input);
+    }
+
+    String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Prints the objective value of the starting basis and after every iteration
+fn print_objectives<N: Display>(result: &Result<Solution<N>, SimplexMethodError>) {
+    if let Ok(solution) = result {
+        for (i, z) in solution.objective_history().iter().enumerate() {
+            println!("Iteration {i}: z = {z}");
+        }
+    }
+}
+
+/// Checks the optimality conditions of the solution, any violation being a bug in the solver
+fn check_optimality<N>(result: &Result<Solution<N>, SimplexMethodError>)
+where
+    N: Display + Num + NumAssign + Clone + PartialOrd,
+{
+    match result.as_ref().map(Solution::verify_optimality_conditions) {
+        Ok(Err(no_duals @ OptimalityViolation::NoDuals)) => {
+            eprintln!("Cannot validate the duals: {no_duals}")
+        }
+        Ok(Err(violation)) => {
+            eprintln!("Optimality check failed, this is a bug in the solver: {violation}");
+            exit(4);
+        }
+        Ok(Ok(())) | Err(_) => (),
+    }
+}
+
+/// Writes the pivots made while solving to `path` as CSV
+fn write_pivot_log<N: Display>(result: &Result<Solution<N>, SimplexMethodError>, path: &str) {
+    if let Ok(solution) = result {
+        if let Err(err) = write(path, solution.pivot_log()) {
+            eprintln!("Cannot write the pivot log: {err}");
+            exit(1);
+        }
+    }
+}
+
+fn report<N>(
+    result: Result<Solution<N>, SimplexMethodError>,
+    style: OutputStyle,
+    format: OutputFormat,
+) where
+    N: Display + Num + NumAssign + Clone,
+{
+    match result {
+        Ok(solution) if style.solution() => match format {
+            OutputFormat::Text => println!("{solution}"),
+            OutputFormat::Markdown => print!("{}", solution.to_markdown()),
+            #[cfg(feature = "serde")]
+            OutputFormat::Json => println!("{}", solution.to_json()),
+        },
+        Ok(_) => (),
+        Err(err) => {
+            eprintln!("Cannot get solution: {err}");
+            exit(match err {
+                SimplexMethodError::TimeLimitExceeded
+                | SimplexMethodError::IterationLimitExceeded(_) => 3,
+                _ => 1,
+            });
+        }
+    }
+}
+
+fn render<N>(result: &Result<Solution<N>, SimplexMethodError>) -> String
+where
+    Solution<N>: Display,
+{
+    match result {
+        Ok(solution) => solution.to_string(),
+        Err(err) => format!("Cannot get solution: {err}\n"),
+    }
+}
+
+/// Solves `task`, then keeps reading commands from `input` that modify and re-solve it:
+/// - `add <restriction>` appends a restriction written as in the input file
+/// - `fix x<index> <value>` pins a variable to a value
+/// - `binding` lists the restrictions binding at the last solution
+/// - `range x<index>` shows how far the objective coefficient of a basic variable may move
+/// - `dual` prints the dual of the task
+/// - `resolve` solves the modified task again
+/// - `quit` stops the loop
+fn repl(mut task: Task, input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    let mut last = solve::<Rational64>(task.clone(), OutputStyle::SolutionOnly, None);
+    write!(output, "{}", render(&last))?;
+
+    for line in input.lines() {
+        let line = line?;
+        let (command, rest) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+
+        match command {
+            "" => (),
+            "add" => match rest.parse::<Restriction>() {
+                Ok(restriction) => {
+                    task.restrictions.push(restriction);
+                    writeln!(output, "Added restriction {}", task.restrictions.len())?;
+                }
+                Err(err) => writeln!(output, "Cannot parse restriction: {err}")?,
+            },
+            "fix" => {
+                let parsed = rest.split_once(' ').and_then(|(var, value)| {
+                    let index = var.trim().strip_prefix('x')?.parse().ok()?;
+                    Some((index, value.trim().parse().ok()?))
+                });
+                match parsed {
+                    Some((index, value)) => {
+                        task.fix_variable(index, value);
+                        writeln!(output, "Fixed x{index} = {value}")?;
+                    }
+                    None => writeln!(output, "Expected `fix x<index> <value>`")?,
+                }
+            }
+            "binding" => match &last {
+                Ok(solution) => {
+                    let binding = solution
+                        .binding_constraints()
+                        .iter()
+                        .map(|i| (i + 1).to_string())
+                        .collect::<Vec<_>>();
+                    writeln!(output, "Binding restrictions: {}", binding.join(", "))?;
+                }
+                Err(err) => writeln!(output, "No solution to inspect: {err}")?,
+            },
+            "range" => {
+                let column = rest.trim().strip_prefix('x').and_then(|x| x.parse::<usize>().ok());
+                match (&last, column) {
+                    (Ok(solution), Some(index)) => {
+                        let range = solution
+                            .objective_ranging()
+                            .into_iter()
+                            .find(|(column, _, _)| *column + 1 == index);
+                        match range {
+                            Some((_, lower, upper)) => writeln!(
+                                output,
+                                "Coefficient of x{index} keeps the basis within [{}, {}]",
+                                lower.map_or("-inf".to_owned(), |x| x.to_string()),
+                                upper.map_or("inf".to_owned(), |x| x.to_string()),
+                            )?,
+                            None => writeln!(output, "x{index} is not basic at the solution")?,
+                        }
+                    }
+                    (Ok(_), None) => writeln!(output, "Expected `range x<index>`")?,
+                    (Err(err), _) => writeln!(output, "No solution to inspect: {err}")?,
+                }
+            }
+            "dual" => write!(output, "{}", task.dual_to_dsl())?,
+            "resolve" => {
+                last = solve::<Rational64>(task.clone(), OutputStyle::SolutionOnly, None);
+                write!(output, "{}", render(&last))?;
+            }
+            "quit" | "exit" => break,
+            _ => writeln!(
+                output,
+                "Unknown command `{command}`, expected add, fix, binding, range, dual, resolve or quit"
+            )?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use simplex::parser::Task;
+
+    use crate::{integer_indices, repl};
+
+    #[test]
+    fn test_integer_indices() {
+        assert_eq!(integer_indices(&[1, 2], 1, 2), Ok(vec![1, 2]));
+        assert_eq!(integer_indices(&[0, 1], 0, 2), Ok(vec![1, 2]));
+        assert!(integer_indices(&[0], 1, 2).is_err());
+        assert!(integer_indices(&[9], 1, 2).is_err());
+        assert!(integer_indices(&[2], 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_repl() {
+        let task: Task = "x1 <= 4\nx2 <= 3\nz = x1 + x2 -> max".parse().unwrap();
+        let commands = [
+            "binding",
+            "range x1",
+            "range x3",
+            "dual",
+            "add x1 + x2 <= 5",
+            "resolve",
+            "fix x2 1",
+            "resolve",
+            "binding",
+            "foo",
+            "quit",
+            "resolve",
+        ]
+        .join("\n");
+        let mut output = vec![];
+
+        repl(task, commands.as_bytes(), &mut output).unwrap();
 
-    println!("{solution}");
+        let output = String::from_utf8(output).unwrap();
+        let optima = output
+            .lines()
+            .filter(|x| x.starts_with("Optimal z is"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            optima,
+            vec!["Optimal z is: 7", "Optimal z is: 5", "Optimal z is: 5"]
+        );
+        assert!(output.contains("Binding restrictions: 1, 2\n"));
+        assert!(output.contains("Coefficient of x1 keeps the basis within [0, inf]\n"));
+        assert!(output.contains("x3 is not basic at the solution\n"));
+        assert!(output.contains("y1 >= 1\ny2 >= 1\n"));
+        assert!(output.contains("Added restriction 3\n"));
+        assert!(output.contains("Binding restrictions: 1, 3, 4\n"));
+        assert!(output.contains("Fixed x2 = 1\n"));
+        assert!(output.contains("Unknown command `foo`"));
+    }
 }
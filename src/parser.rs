@@ -1,17 +1,26 @@
-use std::{fmt::Debug, str::FromStr};
+use std::{
+    collections::BTreeSet,
+    fmt::{Debug, Display, Write},
+    str::FromStr,
+};
 
 use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case},
     character::complete::char,
-    character::complete::{line_ending, multispace0, one_of},
-    combinator::{opt, recognize},
-    error::{context, ContextError, ParseError},
+    character::complete::{alphanumeric1, line_ending, multispace0, one_of, space0, space1},
+    combinator::{all_consuming, opt, recognize, verify},
+    error::{context, ContextError, ErrorKind, ParseError, VerboseError, VerboseErrorKind},
     multi::{many0, many1, separated_list1},
-    sequence::{delimited, preceded, terminated},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
     IResult, Parser,
 };
-use num::{One, Rational64};
+use num::{CheckedDiv, CheckedMul, One, Rational64, Zero};
+
+use crate::errors::{SimplexBuildError, SimplexParseErr};
+
+pub mod dimacs;
+pub mod lp_format;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Method {
@@ -20,43 +29,126 @@ pub enum Method {
     SecondPhase,
 }
 
-#[derive(PartialEq, Debug, Clone)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Goal {
     Maximize,
     Minimize,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Clone)]
 pub enum Relation {
     Equal,
     Less,
     Greater,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Term {
     pub coef: Rational64,
     pub index: u64,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct TargetFn {
     pub goal: Goal,
     pub terms: Vec<Term>,
     pub value: Rational64,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Restriction {
     pub relation: Relation,
     pub terms: Vec<Term>,
     pub value: Rational64,
+    /// Name of the `[section]` header the restriction was written under
+    pub section: Option<String>,
+}
+
+impl Restriction {
+    /// Whether this is `a*x >= 0` for a positive `a`, or the same written as `-a*x <= 0`
+    fn is_nonnegativity(&self) -> bool {
+        match (&self.relation, self.terms.as_slice()) {
+            (Relation::Greater, [term]) => self.value.is_zero() && term.coef > Rational64::zero(),
+            (Relation::Less, [term]) => self.value.is_zero() && term.coef < Rational64::zero(),
+            _ => false,
+        }
+    }
+
+    /// Whether `point`, holding the value of `x{i}` at `i - 1`, satisfies the restriction
+    pub fn holds_at(&self, point: &[Rational64]) -> bool {
+        let lhs = self
+            .terms
+            .iter()
+            .map(|x| x.coef * point.get(x.index as usize - 1).copied().unwrap_or_default())
+            .sum::<Rational64>();
+        match self.relation {
+            Relation::Equal => lhs == self.value,
+            Relation::Less => lhs <= self.value,
+            Relation::Greater => lhs >= self.value,
+        }
+    }
 }
 
+/// Piecewise-linear objective term given by its `(x, f(x))` breakpoints
 #[derive(Debug, PartialEq)]
+pub struct PiecewiseTerm {
+    pub index: u64,
+    pub breakpoints: Vec<(Rational64, Rational64)>,
+}
+
+/// Limit on a single variable replacing the default `x >= 0`, written after the objective
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bound {
+    pub index: u64,
+    pub kind: BoundKind,
+}
+
+impl Bound {
+    /// Whether the bound lets the variable take negative values, so `x >= 0` no longer holds
+    pub fn allows_negative(&self) -> bool {
+        match self.kind {
+            BoundKind::Free => true,
+            BoundKind::Lower(limit) => limit < Rational64::zero(),
+            BoundKind::Upper(_) => false,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BoundKind {
+    /// `x3 free`, the variable may take any sign
+    Free,
+    /// `x2 <= 10`
+    Upper(Rational64),
+    /// `x2 >= 1`
+    Lower(Rational64),
+}
+
+/// Options of the input format
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct ParserConfig {
+    /// Index of the first variable, `x1` by default or `x0` for a 0-based model
+    pub index_base: u64,
+}
+
+impl Default for ParserConfig {
+    fn default() -> Self {
+        Self { index_base: 1 }
+    }
+}
+
+/// Unit suffixes a number may carry, like `100$` or `5kg`. They are only for the reader and are
+/// dropped while parsing, any other suffix is an error.
+pub const UNITS: &[&str] = &["$", "€", "kg"];
+
+/// How many times the number of terms a variable index may reach, see [`Task::check_indices`]
+pub const DEFAULT_INDEX_RATIO: u64 = 100;
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Task {
     pub restrictions: Vec<Restriction>,
     pub target_fn: TargetFn,
+    pub bounds: Vec<Bound>,
     pub method: Method
 }
 
@@ -79,6 +171,17 @@ where
         .map(|x| (x.0, x.1.parse().unwrap()))
 }
 
+/// One of [`UNITS`]
+fn unit<'a, E>(s: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str>,
+{
+    UNITS
+        .iter()
+        .find_map(|unit| s.strip_prefix(unit).map(|rest| (rest, *unit)))
+        .ok_or_else(|| nom::Err::Error(E::from_error_kind(s, ErrorKind::Tag)))
+}
+
 fn coefficient<'a, E>() -> impl Parser<&'a str, Rational64, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -86,20 +189,50 @@ where
     context("coefficient", move |s| {
         let (s, sign) = opt(one_of("+-")).parse(s)?;
         let (s, whole) = decimal.parse(s)?;
-        let (s, trunc) = opt(|s| {
-            let (s, _) = tag(".").parse(s)?;
-            opt(decimal).parse(s)
-        })
-        .parse(s)?;
+        // `a/b` is kept exact instead of going through a decimal
+        let (s, denom) = opt(preceded(char('/'), verify(decimal, |x| *x != 0))).parse(s)?;
+        let (s, number) = match denom {
+            Some(denom) => (s, Rational64::new(whole as i64, denom as i64)),
+            None => {
+                let (s, trunc) = opt(|s| {
+                    let (s, _) = tag(".").parse(s)?;
+                    opt(decimal).parse(s)
+                })
+                .parse(s)?;
 
-        let whole = whole as i64;
-        let trunc = trunc.flatten().unwrap_or(0);
-        let (power, trunc) = if trunc == 0 {
-            (1, 0)
-        } else {
-            (10_i64.pow(trunc.ilog10() + 1), trunc as i64)
+                let whole = whole as i64;
+                let trunc = trunc.flatten().unwrap_or(0);
+                let (power, trunc) = if trunc == 0 {
+                    (1, 0)
+                } else {
+                    (10_i64.pow(trunc.ilog10() + 1), trunc as i64)
+                };
+                let number = Rational64::new_raw(whole, 1) + Rational64::new(trunc, power);
+
+                // `1.5e3` or `2E-2`, scaled exactly by a power of ten
+                let (s, exponent) =
+                    opt(preceded(one_of("eE"), pair(opt(one_of("+-")), decimal))).parse(s)?;
+                let number = match exponent {
+                    None => Some(number),
+                    Some((sign, exponent)) => u32::try_from(exponent)
+                        .ok()
+                        .and_then(|x| 10_i64.checked_pow(x))
+                        .map(Rational64::from_integer)
+                        .and_then(|power| match sign {
+                            Some('-') => number.checked_div(&power),
+                            _ => number.checked_mul(&power),
+                        }),
+                };
+                let Some(number) = number else {
+                    return Err(nom::Err::Failure(E::from_error_kind(
+                        s,
+                        ErrorKind::TooLarge,
+                    )));
+                };
+                (s, number)
+            }
         };
-        let number = Rational64::new_raw(whole, 1) + Rational64::new(trunc, power);
+        let (s, _) = opt(unit).parse(s)?;
 
         Ok((
             s,
@@ -113,7 +246,21 @@ where
     .or(char('-').map(|_| (-1).into()))
 }
 
-/// <0..9>+( *'*' *)?x<0..9>+
+/// x(<0..9>+|'[' *<0..9>+ *']')
+fn variable<'a, E>() -> impl Parser<&'a str, u64, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "variable",
+        preceded(
+            tag_no_case("x"),
+            alt((delimited(char('['), ws(decimal), char(']')), decimal)),
+        ),
+    )
+}
+
+/// <0..9>+( *'*' *| *)[variable]
 fn term<'a, E>() -> impl Parser<&'a str, Term, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -121,8 +268,7 @@ where
     context("term", move |s| {
         let (s, coef) = opt(coefficient()).parse(s)?;
         let (s, _) = opt(ws(tag("*"))).parse(s)?;
-        let (s, _) = tag_no_case("x").parse(s)?;
-        let (s, index) = decimal(s)?;
+        let (s, index) = preceded(space0, variable()).parse(s)?;
 
         Ok((
             s,
@@ -134,74 +280,394 @@ where
     })
 }
 
-/// 'z' *'=' *([inner] *'+')+ *-> *('max'|'min')
-fn target_fn<'a, E>() -> impl Parser<&'a str, TargetFn, E>
+/// (<coefficient>( *'*' *)?)?'(' *[sum] *')'
+///
+/// The coefficient, 1 if omitted, is distributed over the grouped terms
+fn group<'a, E>(s: &'a str) -> IResult<&'a str, Vec<Term>, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
-    context("target_fn", |s| {
-        let (s, _) = tag_no_case("z").parse(s)?;
-        let (s, _) = ws(tag("=")).parse(s)?;
-        let (s, terms) = separated_list1(ws(char('+')), term()).parse(s)?;
-        let (s, _) = ws(tag("->")).parse(s)?;
-        let (s, goal) = alt((tag_no_case("max"), tag_no_case("min"))).parse(s)?;
+    let (s, coef) = opt(coefficient()).parse(s)?;
+    let (s, _) = opt(ws(tag("*"))).parse(s)?;
+    let (s, terms) = delimited(
+        terminated(char('('), multispace0),
+        sum(),
+        preceded(multispace0, char(')')),
+    )
+    .parse(s)?;
+    let coef = coef.unwrap_or(Rational64::one());
+
+    Ok((
+        s,
+        terms
+            .into_iter()
+            .map(|x| Term {
+                coef: x.coef * coef,
+                index: x.index,
+            })
+            .collect(),
+    ))
+}
+
+/// [term]( *'..' *[variable])?
+///
+/// A range like `2x[3]..x[5]` is sugar for `2x3 + 2x4 + 2x5`
+fn range<'a, E>() -> impl Parser<&'a str, Vec<Term>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("range", |s| {
+        let (s, first) = term().parse(s)?;
+        let (s, last) = opt(preceded(ws(tag("..")), variable())).parse(s)?;
+
+        match last {
+            None => Ok((s, vec![first])),
+            Some(last) if last >= first.index => Ok((
+                s,
+                (first.index..=last)
+                    .map(|index| Term {
+                        coef: first.coef,
+                        index,
+                    })
+                    .collect(),
+            )),
+            Some(_) => Err(nom::Err::Error(E::from_error_kind(s, ErrorKind::Verify))),
+        }
+    })
+}
+
+/// [group]|[range]
+fn summand<'a, E>() -> impl Parser<&'a str, Vec<Term>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("summand", alt((group, range())))
+}
+
+/// [summand]( *('+'|'-') *[summand])*
+///
+/// A summand after `-` has all of its coefficients negated
+fn sum<'a, E>() -> impl Parser<&'a str, Vec<Term>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    move |s| {
+        let (s, first) = summand().parse(s)?;
+        let (s, rest) = many0(pair(ws(one_of("+-")), summand())).parse(s)?;
+
+        let rest = rest.into_iter().flat_map(|(sign, terms)| {
+            terms.into_iter().map(move |x| match sign {
+                '-' => Term {
+                    coef: -x.coef,
+                    index: x.index,
+                },
+                _ => x,
+            })
+        });
+        Ok((s, first.into_iter().chain(rest).collect()))
+    }
+}
+
+/// [summand]|[coefficient]
+///
+/// A bare number is a constant, given as the second element
+fn affine_summand<'a, E>() -> impl Parser<&'a str, (Vec<Term>, Rational64), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    alt((
+        summand().map(|x| (x, Rational64::zero())),
+        coefficient().map(|x| (vec![], x)),
+    ))
+}
+
+/// [affine_summand]( *('+'|'-') *[affine_summand])*
+///
+/// Like [sum], but the constants are added up apart from the terms
+fn affine_sum<'a, E>() -> impl Parser<&'a str, (Vec<Term>, Rational64), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    move |s| {
+        let (s, (mut terms, mut value)) = affine_summand().parse(s)?;
+        let (s, rest) = many0(pair(ws(one_of("+-")), affine_summand())).parse(s)?;
+
+        for (sign, (summand, constant)) in rest {
+            let sign = match sign {
+                '-' => -Rational64::one(),
+                _ => Rational64::one(),
+            };
+            terms.extend(summand.into_iter().map(|x| Term {
+                coef: x.coef * sign,
+                index: x.index,
+            }));
+            value += constant * sign;
+        }
+        Ok((s, (terms, value)))
+    }
+}
+
+/// 'maximize'|'minimize'|'max'|'min'
+fn goal<'a, E>() -> impl Parser<&'a str, Goal, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("goal", |s| {
+        let (s, goal): (_, &str) = alt((
+            tag_no_case("maximize"),
+            tag_no_case("minimize"),
+            tag_no_case("max"),
+            tag_no_case("min"),
+        ))
+        .parse(s)?;
 
         Ok((
             s,
-            TargetFn {
-                goal: if goal.to_lowercase() == "max" {
-                    Goal::Maximize
-                } else {
-                    Goal::Minimize
-                },
-                terms,
-                value: Default::default(),
+            if goal.to_lowercase().starts_with("max") {
+                Goal::Maximize
+            } else {
+                Goal::Minimize
             },
         ))
     })
 }
 
-/// '=='|'<='|'>='
+/// ('goal'|'sense') *':' *[goal]
+fn goal_directive<'a, E>() -> impl Parser<&'a str, Goal, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "goal_directive",
+        preceded(
+            alt((tag_no_case("goal"), tag_no_case("sense"))),
+            preceded(ws(char(':')), goal()),
+        ),
+    )
+}
+
+/// 'z' *'=' *[affine_sum] *(-> *[goal])?
+fn objective<'a, E>() -> impl Parser<&'a str, ((Vec<Term>, Rational64), Option<Goal>), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("objective", |s| {
+        let (s, _) = tag_no_case("z").parse(s)?;
+        let (s, _) = ws(tag("=")).parse(s)?;
+        let (s, terms) = affine_sum().parse(s)?;
+        let (s, goal) = opt(preceded(ws(tag("->")), goal())).parse(s)?;
+
+        Ok((s, (terms, goal)))
+    })
+}
+
+/// ([goal_directive] *\n)?([goal] +)?[objective](\n *[goal_directive])?
+///
+/// The goal may come from the directive, a leading keyword like `maximize z = ...`, the `->`
+/// suffix or several of them if they agree
+fn target_fn<'a, E>() -> impl Parser<&'a str, TargetFn, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("target_fn", |s| {
+        let (rest, before) = opt(terminated(goal_directive(), line_ending)).parse(s)?;
+        let (rest, leading) = opt(terminated(goal(), space1)).parse(rest)?;
+        let (rest, ((terms, value), suffix)) = objective().parse(rest)?;
+        let (rest, after) = opt(preceded(line_ending, goal_directive())).parse(rest)?;
+
+        let mut goals = [before, leading, after, suffix].into_iter().flatten();
+        let goal = match goals.next() {
+            Some(goal) if goals.any(|x| x != goal) => {
+                return Err(nom::Err::Failure(E::add_context(
+                    s,
+                    "goal of the directive",
+                    E::from_error_kind(s, ErrorKind::Verify),
+                )))
+            }
+            Some(goal) => goal,
+            // an objective without a goal must not pass for a task with no objective at all
+            None => {
+                return Err(nom::Err::Failure(E::add_context(
+                    s,
+                    "goal",
+                    E::from_error_kind(rest, ErrorKind::Tag),
+                )))
+            }
+        };
+
+        Ok((rest, TargetFn { goal, terms, value }))
+    })
+}
+
+/// '=='|'<='|'>='|'='|'≤'|'≥'
 fn relation<'a, E>() -> impl Parser<&'a str, Relation, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
     context("relation", |s| {
-        let (rest, choise) = tag("==").or(tag("<=")).or(tag(">=")).parse(s)?;
+        // textbooks and other tools write a single `=` and the unicode signs
+        let (rest, choise) = alt((
+            tag("=="),
+            tag("<="),
+            tag(">="),
+            tag("="),
+            tag("≤"),
+            tag("≥"),
+        ))
+        .parse(s)?;
 
         Ok((
             rest,
             match choise {
-                "==" => Relation::Equal,
-                "<=" => Relation::Less,
-                ">=" => Relation::Greater,
+                "==" | "=" => Relation::Equal,
+                "<=" | "≤" => Relation::Less,
+                ">=" | "≥" => Relation::Greater,
                 _ => unreachable!(),
             },
         ))
     })
 }
 
-/// ([term] *'+') *[relation] *[value]
+/// [affine_sum] *[relation] *[value]
 fn restriction<'a, E>() -> impl Parser<&'a str, Restriction, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
 {
     context("restriction", |s| {
-        let (s, terms) = separated_list1(ws(char('+')), term()).parse(s)?;
+        let (s, (terms, constant)) = affine_sum().parse(s)?;
         let (s, relation) = ws(relation()).parse(s)?;
         let (s, value) = preceded(multispace0, coefficient()).parse(s)?;
 
+        // constants never become columns, they are moved to the right-hand side instead
         Ok((
             s,
             Restriction {
                 relation,
                 terms,
-                value,
+                value: value - constant,
+                section: None,
+            },
+        ))
+    })
+}
+
+/// '[' *<a..z0..9_>+ *']'
+fn section<'a, E>() -> impl Parser<&'a str, String, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "section",
+        delimited(
+            char('['),
+            ws(recognize(many1(alt((alphanumeric1, tag("_")))))),
+            char(']'),
+        )
+        .map(str::to_owned),
+    )
+}
+
+/// 'soft' +(<a..z0..9>+ *':')? *[restriction] +'penalty' +[coefficient]
+///
+/// The label only names the constraint for the reader and is not kept
+fn soft_restriction<'a, E>() -> impl Parser<&'a str, (Restriction, Rational64), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("soft restriction", |s| {
+        let (s, _) = terminated(tag_no_case("soft"), space1).parse(s)?;
+        let (s, _) = opt(terminated(alphanumeric1, ws(char(':')))).parse(s)?;
+        let (s, restriction) = restriction().parse(s)?;
+        let (s, _) = delimited(space1, tag_no_case("penalty"), space1).parse(s)?;
+        let (s, penalty) = coefficient().parse(s)?;
+
+        Ok((s, (restriction, penalty)))
+    })
+}
+
+/// 'simplex' +([variable] +)*[variable]
+///
+/// Shares of an allocation, expanding to the equality that they sum to 1. Being variables, they
+/// are nonnegative already.
+fn allocation<'a, E>() -> impl Parser<&'a str, Restriction, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("allocation", |s| {
+        let (s, _) = terminated(tag_no_case("simplex"), space1).parse(s)?;
+        let (s, indices) = separated_list1(space1, variable()).parse(s)?;
+        let (s, _) = space0(s)?;
+
+        Ok((
+            s,
+            Restriction {
+                relation: Relation::Equal,
+                terms: indices
+                    .into_iter()
+                    .map(|index| Term {
+                        coef: Rational64::one(),
+                        index,
+                    })
+                    .collect(),
+                value: Rational64::one(),
+                section: None,
             },
         ))
     })
 }
 
+/// 'pwl' *x<0..9>+ *':' *('(' *[coefficient] *',' *[coefficient] *')' *)+
+fn piecewise<'a, E>() -> impl Parser<&'a str, PiecewiseTerm, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("piecewise", |s| {
+        let (s, _) = tag_no_case("pwl").parse(s)?;
+        let (s, _) = preceded(space0, tag_no_case("x")).parse(s)?;
+        let (s, index) = decimal(s)?;
+        let (s, _) = ws(char(':')).parse(s)?;
+        let (s, breakpoints) = many1(preceded(
+            space0,
+            delimited(
+                char('('),
+                separated_pair(ws(coefficient()), char(','), ws(coefficient())),
+                char(')'),
+            ),
+        ))
+        .parse(s)?;
+
+        Ok((s, PiecewiseTerm { index, breakpoints }))
+    })
+}
+
+/// x[index] free | x[index] *(<= | >=) *[value]
+fn bound<'a, E>() -> impl Parser<&'a str, Bound, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context("bound", |s| {
+        let (s, _) = preceded(space0, tag_no_case("x")).parse(s)?;
+        let (s, index) = decimal(s)?;
+        let (s, kind) = alt((
+            preceded(space1, tag_no_case("free")).map(|_| BoundKind::Free),
+            preceded(
+                pair(space0, alt((tag("<="), tag("≤")))),
+                preceded(space0, coefficient()),
+            )
+            .map(BoundKind::Upper),
+            preceded(
+                pair(space0, alt((tag(">="), tag("≥")))),
+                preceded(space0, coefficient()),
+            )
+            .map(BoundKind::Lower),
+        ))
+        .parse(s)?;
+        let (s, _) = space0(s)?;
+
+        Ok((s, Bound { index, kind }))
+    })
+}
+
 fn method<'a, E>() -> impl Parser<&'a str, Method, E>
 where
     E: ParseError<&'a str> + ContextError<&'a str>,
@@ -224,45 +690,569 @@ where
     })
 }
 
+/// A line of the restrictions block
+enum Line {
+    Section(String),
+    Restriction(Restriction, Option<Rational64>),
+}
+
+/// A line break or `;` between restrictions, so that several of them fit on one line
+fn separator<'a, E>(s: &'a str) -> IResult<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str>,
+{
+    alt((line_ending, recognize(ws(char(';'))))).parse(s)
+}
+
 impl Task {
     fn parse<'a, E>() -> impl Parser<&'a str, Task, E>
     where
         E: ParseError<&'a str> + ContextError<&'a str>,
     {
         context("task", |s| {
-            let (s, restrictions) = separated_list1(line_ending, restriction()).parse(s)?;
-            let (s, _) = line_ending(s)?;
-            let (s, target_fn) = target_fn().parse(s)?;
+            // the objective may as well open the task
+            let (s, leading) = opt(terminated(target_fn(), separator)).parse(s)?;
+            let (s, lines) = separated_list1(
+                separator,
+                alt((
+                    section().map(Line::Section),
+                    allocation().map(|x| Line::Restriction(x, None)),
+                    soft_restriction().map(|(x, penalty)| Line::Restriction(x, Some(penalty))),
+                    restriction().map(|x| Line::Restriction(x, None)),
+                )),
+            )
+            .parse(s)?;
+            let (mut restrictions, mut penalties) = (vec![], vec![]);
+            let mut section = None;
+            for line in lines {
+                match line {
+                    Line::Section(name) => section = Some(name),
+                    Line::Restriction(restriction, penalty) => {
+                        restrictions.push(Restriction {
+                            section: section.clone(),
+                            ..restriction
+                        });
+                        penalties.push(penalty);
+                    }
+                }
+            }
+            // without an objective only the feasibility of the restrictions is of interest
+            let (s, target_fn) = match leading {
+                Some(target_fn) => (s, Some(target_fn)),
+                None => opt(preceded(separator, target_fn())).parse(s)?,
+            };
+            let target_fn = target_fn.unwrap_or(TargetFn {
+                goal: Goal::Minimize,
+                terms: vec![],
+                value: Rational64::zero(),
+            });
+
+            let (s, piecewise) = many0(preceded(line_ending, piecewise())).parse(s)?;
+            let (s, bounds) = many0(preceded(line_ending, bound())).parse(s)?;
             let (s, _) = opt(line_ending).parse(s)?;
             let (s, method) = opt(method()).parse(s)?;
 
-            Ok((
-                s,
-                Self {
-                    restrictions,
-                    target_fn,
-                    method: method.unwrap_or(Method::Simple)
-                },
-            ))
+            let mut task = Self {
+                restrictions,
+                target_fn,
+                bounds,
+                method: method.unwrap_or(Method::Simple)
+            };
+            for (row, penalty) in penalties.into_iter().enumerate() {
+                if let Some(penalty) = penalty {
+                    task.soften(row, penalty);
+                }
+            }
+            for term in piecewise {
+                task.add_piecewise(term);
+            }
+            task.drop_nonnegativity();
+
+            Ok((s, task))
         })
     }
+
+    /// Models a piecewise-linear objective term with the lambda (convex combination) formulation:
+    /// `x = sum(l_k * x_k)`, `sum(l_k) == 1` and `sum(l_k * f_k)` is added to the objective.
+    ///
+    /// The result is only exact for convex costs when minimizing or concave ones when maximizing.
+    pub fn add_piecewise(&mut self, term: PiecewiseTerm) {
+        let first = self.next_index().max(term.index + 1);
+        let lambdas = first..first + term.breakpoints.len() as u64;
+
+        let mut link = vec![Term {
+            coef: Rational64::one(),
+            index: term.index,
+        }];
+        link.extend(
+            lambdas
+                .clone()
+                .zip(&term.breakpoints)
+                .map(|(index, (x, _))| Term { coef: -x, index }),
+        );
+        self.restrictions.push(Restriction {
+            relation: Relation::Equal,
+            terms: link,
+            value: Rational64::zero(),
+            section: None,
+        });
+        self.restrictions.push(Restriction {
+            relation: Relation::Equal,
+            terms: lambdas
+                .clone()
+                .map(|index| Term {
+                    coef: Rational64::one(),
+                    index,
+                })
+                .collect(),
+            value: Rational64::one(),
+            section: None,
+        });
+        self.target_fn.terms.extend(
+            lambdas
+                .zip(term.breakpoints)
+                .map(|(index, (_, cost))| Term { coef: cost, index }),
+        );
+    }
+
+    /// Presolve: a restriction `a*x == b` with a single term pins `x` to `b / a`, so `x` is
+    /// replaced by that value everywhere and the restriction is dropped, repeating while new
+    /// singletons show up. Negative values are left for the solver to find infeasible and the
+    /// last restriction is kept so that there is still a tableau to build.
+    ///
+    /// Returns the eliminated variables with their values.
+    pub fn substitute_singletons(&mut self) -> Vec<(u64, Rational64)> {
+        let mut fixed = vec![];
+
+        while self.restrictions.len() > 1 {
+            let singleton = self.restrictions.iter().position(|x| {
+                x.relation == Relation::Equal
+                    && matches!(x.terms.as_slice(), [term] if !term.coef.is_zero()
+                        && x.value / term.coef >= Rational64::zero())
+            });
+            let Some(row) = singleton else {
+                break;
+            };
+            let restriction = self.restrictions.remove(row);
+            let (index, value) = (
+                restriction.terms[0].index,
+                restriction.value / restriction.terms[0].coef,
+            );
+
+            for restriction in &mut self.restrictions {
+                for term in restriction.terms.iter().filter(|x| x.index == index) {
+                    restriction.value -= term.coef * value;
+                }
+                restriction.terms.retain(|x| x.index != index);
+            }
+            for term in self.target_fn.terms.iter().filter(|x| x.index == index) {
+                self.target_fn.value += term.coef * value;
+            }
+            self.target_fn.terms.retain(|x| x.index != index);
+            fixed.push((index, value));
+        }
+
+        fixed
+    }
+
+    /// Lets the `row`-th restriction be violated by a deviation variable charged `penalty` per unit
+    /// in the objective: `a <= b` becomes `a - d <= b`, `a >= b` becomes `a + d >= b` and an
+    /// equality gets a deviation for each direction.
+    pub fn soften(&mut self, row: usize, penalty: Rational64) {
+        let first = self.next_index();
+        let signs: &[i64] = match self.restrictions[row].relation {
+            Relation::Less => &[-1],
+            Relation::Greater => &[1],
+            Relation::Equal => &[-1, 1],
+        };
+        let penalty = match self.target_fn.goal {
+            Goal::Maximize => -penalty,
+            Goal::Minimize => penalty,
+        };
+
+        for (index, &sign) in (first..).zip(signs) {
+            self.restrictions[row].terms.push(Term {
+                coef: sign.into(),
+                index,
+            });
+            self.target_fn.terms.push(Term {
+                coef: penalty,
+                index,
+            });
+        }
+    }
+
+    /// Drops rows like `x3 >= 0`, which only grow the tableau as every variable is nonnegative
+    /// anyway, unless a bound lets that variable go below zero
+    pub(crate) fn drop_nonnegativity(&mut self) {
+        let bounds = &self.bounds;
+        self.restrictions.retain(|x| {
+            !x.is_nonnegativity()
+                || bounds
+                    .iter()
+                    .any(|bound| bound.index == x.terms[0].index && bound.allows_negative())
+        });
+    }
+
+    /// The index right after the largest variable used anywhere in the task
+    pub fn next_index(&self) -> u64 {
+        self.restrictions
+            .iter()
+            .flat_map(|x| &x.terms)
+            .chain(&self.target_fn.terms)
+            .map(|x| x.index)
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// Rejects variable indices above `ratio` times the number of terms, since every index up to
+    /// the largest one becomes a tableau column and a typo like `x1000000` would exhaust memory.
+    pub fn check_indices(&self, ratio: u64) -> Result<(), SimplexBuildError> {
+        let terms = self
+            .restrictions
+            .iter()
+            .flat_map(|x| &x.terms)
+            .chain(&self.target_fn.terms);
+        let limit = ratio.saturating_mul(terms.clone().count() as u64);
+
+        match terms.map(|x| x.index).max() {
+            Some(index) if index > limit => Err(SimplexBuildError::IndexTooLarge { index, limit }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Rejects gaps in the variable indices, like `x1` and `x3` without `x2`. A missing index
+    /// still becomes a zero column, which is intended in a sparse model but usually is a typo.
+    ///
+    /// The missing variables are reported counted from `index_base`, like the input they were
+    /// parsed from with the same [`ParserConfig`].
+    pub fn check_contiguous(&self, index_base: u64) -> Result<(), SimplexBuildError> {
+        let used: BTreeSet<_> = self
+            .restrictions
+            .iter()
+            .flat_map(|x| &x.terms)
+            .chain(&self.target_fn.terms)
+            .map(|x| x.index)
+            .chain(self.bounds.iter().map(|x| x.index))
+            .collect();
+        let last = used.last().copied().unwrap_or(0);
+        let missing: Vec<_> = (1..last)
+            .filter(|x| !used.contains(x))
+            .map(|x| x - 1 + index_base)
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(SimplexBuildError::MissingIndices(missing))
+        }
+    }
+
+    /// Pins `x{index}` to `value` by adding the equality `x{index} == value`.
+    ///
+    /// Equalities have no slack to start the simple method from, so such a task switches to taxes.
+    pub fn fix_variable(&mut self, index: u64, value: Rational64) {
+        self.restrictions.push(Restriction {
+            relation: Relation::Equal,
+            terms: vec![Term {
+                coef: Rational64::one(),
+                index,
+            }],
+            value,
+            section: None,
+        });
+        if self.method == Method::Simple {
+            self.method = Method::Taxes;
+        }
+    }
+}
+
+impl Task {
+    /// Writes the task back in the input format, naming variables `{label}1, {label}2, ...`
+    pub(crate) fn render(&self, label: char) -> String {
+        let sum = |terms: &[Term]| {
+            if terms.is_empty() {
+                return "0".to_owned();
+            }
+            terms
+                .iter()
+                .map(|x| match x.coef {
+                    c if c == Rational64::one() => format!("{label}{}", x.index),
+                    c if c == -Rational64::one() => format!("-{label}{}", x.index),
+                    c => format!("{c}{label}{}", x.index),
+                })
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        let mut out = String::new();
+
+        let mut section = None;
+        for restriction in &self.restrictions {
+            if restriction.section.is_some() && restriction.section != section {
+                section = restriction.section.clone();
+                let _ = writeln!(out, "[{}]", section.as_deref().unwrap_or_default());
+            }
+            let relation = match restriction.relation {
+                Relation::Equal => "==",
+                Relation::Less => "<=",
+                Relation::Greater => ">=",
+            };
+            let _ = writeln!(
+                out,
+                "{} {relation} {}",
+                sum(&restriction.terms),
+                restriction.value
+            );
+        }
+        let goal = match self.target_fn.goal {
+            Goal::Maximize => "max",
+            Goal::Minimize => "min",
+        };
+        let objective = match (self.target_fn.terms.as_slice(), self.target_fn.value) {
+            (terms, value) if value.is_zero() => sum(terms),
+            ([], value) => value.to_string(),
+            (terms, value) => format!("{} + {value}", sum(terms)),
+        };
+        let _ = writeln!(out, "z = {objective} -> {goal}");
+        for bound in &self.bounds {
+            let _ = match &bound.kind {
+                BoundKind::Free => writeln!(out, "{label}{} free", bound.index),
+                BoundKind::Upper(value) => writeln!(out, "{label}{} <= {value}", bound.index),
+                BoundKind::Lower(value) => writeln!(out, "{label}{} >= {value}", bound.index),
+            };
+        }
+        let method = match self.method {
+            Method::Simple => "simple method",
+            Method::Taxes => "taxes",
+            Method::SecondPhase => "second phase",
+        };
+        let _ = writeln!(out, "solve using {method}");
+
+        out
+    }
+}
+
+impl Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.render('x'))
+    }
+}
+
+/// Joins lines ending with a backslash to the next one. The backslash and the line break turn
+/// into as many spaces, so that an offset into the result is also one into `s`.
+fn join_continuations(s: &str) -> String {
+    s.replace("\\\r\n", "   ").replace("\\\n", "  ")
+}
+
+/// Drops `#` and `//` comments along with the spaces before them, and the lines left empty by
+/// that. Besides the text, returns where each remaining line starts in it and in `s`.
+fn strip_comments(s: &str) -> (String, Vec<(usize, usize)>) {
+    let (mut stripped, mut lines) = (String::with_capacity(s.len()), vec![]);
+    let mut start = 0;
+    for line in s.split_inclusive('\n') {
+        let body = line.trim_end_matches(['\r', '\n']);
+        let comment = [body.find('#'), body.find("//")]
+            .into_iter()
+            .flatten()
+            .min();
+        let kept = match comment {
+            Some(at) => body[..at].trim_end(),
+            None => body,
+        };
+        if comment.is_none() || !kept.is_empty() {
+            lines.push((stripped.len(), start));
+            stripped.push_str(kept);
+            stripped.push_str(&line[body.len()..]);
+        }
+        start += line.len();
+    }
+    (stripped, lines)
+}
+
+/// The text [`Task::parse_with`] parses in place of `s`: comments stripped and continued lines
+/// joined. Returns the line starts of [`strip_comments`] along with it.
+fn preprocess(s: &str) -> (String, Vec<(usize, usize)>) {
+    let (stripped, lines) = strip_comments(s);
+    (join_continuations(&stripped), lines)
+}
+
+impl Task {
+    /// Parses a task whose variables are counted from `config.index_base` and renumbers them
+    /// from 1, the way the solver counts columns. A variable below the base is an error.
+    pub fn parse_with(s: &str, config: ParserConfig) -> Result<Self, SimplexParseErr> {
+        Self::parse_verbose(s, config).map_err(|err| locate_error(s, err))
+    }
+
+    fn parse_verbose(
+        s: &str,
+        config: ParserConfig,
+    ) -> Result<Self, nom::Err<nom::error::VerboseError<String>>> {
+        let (text, _) = preprocess(s);
+        // some editors save files with a byte order mark or leave blank lines on top
+        let s = text.strip_prefix('\u{feff}').unwrap_or(&text).trim_start();
+
+        let mut task = all_consuming(terminated(
+            Task::parse::<nom::error::VerboseError<&str>>(),
+            multispace0,
+        ))
+        .parse(s)
+        .map(|x| x.1)
+        .map_err(|x| {
+            x.map(|mut y| {
+                // a line no parser took ends the task early, which leaves only `all_consuming`
+                // complaining at its start, so the line is parsed again to see where it fails
+                if let Some(&(rest, VerboseErrorKind::Nom(ErrorKind::Eof))) = y.errors.first() {
+                    if let Some(mut errors) = line_errors(rest) {
+                        errors.append(&mut y.errors);
+                        y.errors = errors;
+                    }
+                }
+                nom::error::VerboseError {
+                    errors: y
+                        .errors
+                        .into_iter()
+                        .map(|z| (z.0.to_owned(), z.1))
+                        .collect(),
+                }
+            })
+        })?;
+
+        let indices = task
+            .restrictions
+            .iter_mut()
+            .flat_map(|x| &mut x.terms)
+            .chain(&mut task.target_fn.terms)
+            .map(|x| &mut x.index)
+            .chain(task.bounds.iter_mut().map(|x| &mut x.index));
+        for index in indices {
+            if *index < config.index_base {
+                return Err(nom::Err::Failure(nom::error::VerboseError {
+                    errors: vec![(
+                        format!("x{index}"),
+                        nom::error::VerboseErrorKind::Context(
+                            "variable at or above the index base",
+                        ),
+                    )],
+                }));
+            }
+            *index = *index + 1 - config.index_base;
+        }
+
+        Ok(task)
+    }
+}
+
+/// Failure of the single line parser which got the furthest into `line`, if any got further
+/// than its start. The earlier parser wins a tie, restrictions being tried first.
+fn line_errors(line: &str) -> Option<Vec<(&str, VerboseErrorKind)>> {
+    type E<'a> = VerboseError<&'a str>;
+    let start = line.trim_start();
+
+    [
+        restriction::<E>().map(|_| ()).parse(start),
+        soft_restriction::<E>().map(|_| ()).parse(start),
+        allocation::<E>().map(|_| ()).parse(start),
+        section::<E>().map(|_| ()).parse(start),
+        target_fn::<E>().map(|_| ()).parse(start),
+        piecewise::<E>().map(|_| ()).parse(start),
+        bound::<E>().map(|_| ()).parse(start),
+        method::<E>().map(|_| ()).parse(start),
+    ]
+    .into_iter()
+    .filter_map(|x| match x {
+        Err(nom::Err::Error(err) | nom::Err::Failure(err)) => Some(err.errors),
+        _ => None,
+    })
+    .filter(|errors| {
+        errors
+            .first()
+            .is_some_and(|(rest, _)| rest.len() < start.len())
+    })
+    .min_by_key(|errors| errors[0].0.len())
+}
+
+/// Line and column, both counted from 1, of the character `offset` bytes into `input`
+fn line_column(input: &str, offset: usize) -> (usize, usize) {
+    let before = &input[..offset];
+    let line_start = before.rfind('\n').map_or(0, |x| x + 1);
+
+    (
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    )
+}
+
+/// Locates a failure of the parser in `input`, the text that was given to it
+pub(crate) fn locate_error(
+    input: &str,
+    err: nom::Err<nom::error::VerboseError<String>>,
+) -> SimplexParseErr {
+    let errors = match err {
+        nom::Err::Error(x) | nom::Err::Failure(x) => x.errors,
+        nom::Err::Incomplete(_) => vec![],
+    };
+    // the innermost parser that failed comes first, followed by the ones it was part of
+    let expected = errors
+        .iter()
+        .find_map(|(_, kind)| match kind {
+            nom::error::VerboseErrorKind::Context(context) => Some(context.to_string()),
+            _ => None,
+        })
+        .or_else(|| match errors.first() {
+            Some((_, nom::error::VerboseErrorKind::Char(c))) => Some(format!("'{c}'")),
+            Some((_, nom::error::VerboseErrorKind::Nom(ErrorKind::Eof))) => {
+                Some("end of input".to_owned())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| "a task".to_owned());
+    // the remaining input is a suffix of the preprocessed one, except for fragments like the
+    // variable `parse_with` rejected, which are looked up instead
+    let (text, lines) = preprocess(input);
+    let offset = match errors.first() {
+        Some((rest, _)) if text.ends_with(rest.as_str()) => text.len() - rest.len(),
+        Some((rest, _)) => text.find(rest.as_str()).unwrap_or(0),
+        None => text.len(),
+    };
+    // only the ends of lines were cut, so the offset within its line is the same in `input`
+    let offset = match lines.iter().rev().find(|(start, _)| *start <= offset) {
+        Some((start, original)) => original + offset - start,
+        None => input.len(),
+    };
+    let (line, column) = line_column(input, offset);
+
+    SimplexParseErr {
+        expected,
+        line,
+        column,
+    }
 }
 
 impl FromStr for Task {
-    type Err = nom::Err<nom::error::VerboseError<String>>;
+    type Err = SimplexParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Task::parse_with(s, ParserConfig::default())
+    }
+}
+
+impl FromStr for Restriction {
+    type Err = SimplexParseErr;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Task::parse::<nom::error::VerboseError<&str>>()
+        all_consuming(ws(restriction::<nom::error::VerboseError<&str>>()))
             .parse(s)
             .map(|x| x.1)
             .map_err(|x| {
-                x.map(|y| nom::error::VerboseError {
+                let err = x.map(|y| nom::error::VerboseError {
                     errors: y
                         .errors
                         .into_iter()
                         .map(|z| (z.0.to_owned(), z.1))
                         .collect(),
-                })
+                });
+                locate_error(s, err)
             })
     }
 }
@@ -273,15 +1263,22 @@ mod tests {
     use num::Rational64;
     use rstest::rstest;
 
+    use crate::errors::{SimplexBuildError, SimplexParseErr};
     use crate::parser::{
-        coefficient, relation, restriction, target_fn, Goal, Relation, Restriction, TargetFn, Term,
+        coefficient, piecewise, relation, restriction, sum, target_fn, term, Bound, BoundKind,
+        Goal, ParserConfig, PiecewiseTerm, Relation, Restriction, TargetFn, Task, Term,
+        DEFAULT_INDEX_RATIO,
     };
+    use crate::solve_task;
 
     #[rstest]
     #[case("5.2", 5.2)]
     #[case("-555.111", -555.111)]
     #[case("5.", 5.0)]
     #[case("5", 5.0)]
+    #[case("100$", 100.0)]
+    #[case("5kg", 5.0)]
+    #[case("2.5€", 2.5)]
     fn test_coefficient(#[case] num_str: &str, #[case] number: f64) {
         assert_eq!(
             coefficient::<nom::error::Error<&str>>().parse(num_str),
@@ -289,6 +1286,78 @@ mod tests {
         );
     }
 
+    #[rstest]
+    #[case("1.5e3", 1500, 1)]
+    #[case("2e-2", 1, 50)]
+    #[case("-2.5E+1", -25, 1)]
+    #[case("3e0kg", 3, 1)]
+    fn test_scientific(#[case] num_str: &str, #[case] numer: i64, #[case] denom: i64) {
+        assert_eq!(
+            coefficient::<nom::error::Error<&str>>().parse(num_str),
+            Ok(("", Rational64::new(numer, denom)))
+        );
+    }
+
+    #[rstest]
+    #[case("1e19")]
+    #[case("9.5e18")]
+    #[case("1e-4294967296")]
+    fn test_scientific_overflow(#[case] num_str: &str) {
+        assert!(matches!(
+            coefficient::<nom::error::Error<&str>>().parse(num_str),
+            Err(nom::Err::Failure(_))
+        ));
+    }
+
+    #[rstest]
+    #[case("2/3", 2, 3)]
+    #[case("-5/10", -1, 2)]
+    #[case("+4/2", 2, 1)]
+    #[case("1/3$", 1, 3)]
+    fn test_fraction(#[case] num_str: &str, #[case] numer: i64, #[case] denom: i64) {
+        assert_eq!(
+            coefficient::<nom::error::Error<&str>>().parse(num_str),
+            Ok(("", Rational64::new(numer, denom)))
+        );
+    }
+
+    #[rstest]
+    #[case("x3", "x[3]")]
+    #[case("2x3", "2x[ 3 ]")]
+    #[case("-1.5 * x3", "-1.5 * X[3]")]
+    fn test_bracket_index(#[case] plain: &str, #[case] bracketed: &str) {
+        assert_eq!(
+            term::<nom::error::Error<&str>>().parse(plain),
+            term::<nom::error::Error<&str>>().parse(bracketed)
+        );
+    }
+
+    #[rstest]
+    #[case("(x1 + x2)", "x1 + x2")]
+    #[case("( x1 + x2 ) + x3", "x1 + x2 + x3")]
+    #[case("2(x1 + -x2)", "2x1 + -2x2")]
+    #[case("3 * (x1 + 2(x2 + x3))", "3x1 + 6x2 + 6x3")]
+    #[case("-(x1)", "-x1")]
+    #[case("x[3]..x[5]", "x3 + x4 + x5")]
+    #[case("x1 + 2x[2] .. x[3]", "x1 + 2x2 + 2x3")]
+    #[case("x2..x2", "x2")]
+    fn test_index_range(#[case] range: &str, #[case] expanded: &str) {
+        assert_eq!(
+            sum::<nom::error::Error<&str>>().parse(range),
+            sum::<nom::error::Error<&str>>().parse(expanded)
+        );
+    }
+
+    #[rstest]
+    #[case("z = 2x1 + 5 -> max", 5)]
+    #[case("z = 3 + x1 - 2 + x2 -> max", 1)]
+    #[case("z = x1 - 4 -> min", -4)]
+    fn test_objective_constant(#[case] input: &str, #[case] value: i64) {
+        let (_, target) = target_fn::<nom::error::Error<&str>>().parse(input).unwrap();
+
+        assert_eq!(target.value, value.into());
+    }
+
     #[rstest]
     fn test_target_fn() {
         assert_eq!(
@@ -347,6 +1416,199 @@ mod tests {
             .is_err());
     }
 
+    #[rstest]
+    #[case("goal: max\nz = x1")]
+    #[case("z = x1\nsense: max")]
+    #[case("z = x1 -> max")]
+    #[case("Goal : MAX\nz = x1 -> max")]
+    fn test_goal_directive(#[case] input: &str) {
+        assert_eq!(
+            target_fn::<nom::error::Error<&str>>().parse(input),
+            Ok((
+                "",
+                TargetFn {
+                    goal: Goal::Maximize,
+                    terms: vec![Term {
+                        coef: 1.into(),
+                        index: 1
+                    }],
+                    value: Default::default()
+                }
+            ))
+        );
+    }
+
+    #[rstest]
+    fn test_bare_group() {
+        assert_eq!(
+            "(x1 + x2) <= 5".parse::<Restriction>().unwrap(),
+            "x1 + x2 <= 5".parse::<Restriction>().unwrap()
+        );
+        assert_eq!(
+            target_fn::<nom::error::Error<&str>>().parse("z = (x1 + x2) -> max"),
+            target_fn::<nom::error::Error<&str>>().parse("z = x1 + x2 -> max")
+        );
+    }
+
+    #[rstest]
+    fn test_units() {
+        assert_eq!(
+            "100$ x1 <= 5000$".parse::<Restriction>(),
+            "100x1 <= 5000".parse::<Restriction>()
+        );
+        assert!("5lb x1 <= 20".parse::<Restriction>().is_err());
+        assert!("5x1 <= 20lb".parse::<Restriction>().is_err());
+    }
+
+    #[rstest]
+    #[case("-x1 + x2 <= 3", "x2 - x1 <= 3")]
+    #[case("-x1 + x2 <= 3", "x2 + -x1 <= 3")]
+    #[case("x1 + -2x2 + -x3 <= 3", "x1 - 2x2 - x3 <= 3")]
+    #[case("x1 + -2x2 + -x3 <= 3", "x1 - (2x2 + x3) <= 3")]
+    fn test_subtraction(#[case] left: &str, #[case] right: &str) {
+        let sorted = |s: &str| {
+            let mut restriction: Restriction = s.parse().unwrap();
+            restriction.terms.sort_by_key(|x| x.index);
+            restriction
+        };
+
+        assert_eq!(sorted(left), sorted(right));
+    }
+
+    #[rstest]
+    fn test_sections() {
+        let task: Task = "[capacity]\nx1 <= 4\nx2 <= 3\n[ demand ]\nx1 + x2 >= 2\nz = x1 -> max"
+            .parse()
+            .unwrap();
+        let sections = task
+            .restrictions
+            .iter()
+            .map(|x| x.section.as_deref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            sections,
+            vec![Some("capacity"), Some("capacity"), Some("demand")]
+        );
+        assert!(task
+            .render('x')
+            .starts_with("[capacity]\nx1 <= 4\nx2 <= 3\n[demand]\n"));
+    }
+
+    #[rstest]
+    fn test_no_objective() {
+        let task: Task = "x1 + x2 >= 3\nx1 <= 1".parse().unwrap();
+
+        assert_eq!(task.restrictions.len(), 2);
+        assert!(task.target_fn.terms.is_empty());
+    }
+
+    #[rstest]
+    #[case(0, "x0 + 2x1 <= 4\nz = x1 -> max")]
+    #[case(1, "x1 + 2x2 <= 4\nz = x2 -> max")]
+    fn test_index_base(#[case] index_base: u64, #[case] input: &str) {
+        let task = Task::parse_with(input, ParserConfig { index_base }).unwrap();
+
+        assert_eq!(task, "x1 + 2x2 <= 4\nz = x2 -> max".parse().unwrap());
+        assert!(Task::parse_with("x0 <= 1", ParserConfig::default()).is_err());
+    }
+
+    #[rstest]
+    fn test_bounds() {
+        let input = "x1 + x2 <= 4\nz = x1 -> max\nx2 free\nx1 <= 3\nx1 >= 1";
+        let task: Task = input.parse().unwrap();
+
+        // bounds after the objective are kept apart from the restrictions
+        assert_eq!(task.restrictions.len(), 1);
+        assert_eq!(
+            task.bounds,
+            [
+                Bound {
+                    index: 2,
+                    kind: BoundKind::Free
+                },
+                Bound {
+                    index: 1,
+                    kind: BoundKind::Upper(3.into())
+                },
+                Bound {
+                    index: 1,
+                    kind: BoundKind::Lower(1.into())
+                },
+            ]
+        );
+        assert_eq!(task.to_string().parse::<Task>(), Ok(task));
+    }
+
+    #[rstest]
+    #[case("x1 + x2 <= 4\nx1 => 3\nz = x1 -> max", "restriction", 2, 5)]
+    #[case("x1 + x2 <= 4\nx1 <> 4\nz = x1 -> max", "relation", 2, 4)]
+    #[case("x1 <= 4\nz = x1 -> max\nx1 >= 1/0", "end of input", 3, 8)]
+    #[case("x1 <= 4\nz = x1 -> mux", "goal", 2, 7)]
+    #[case("x1 <= 4\nz = x1 -> max\ngoal: min", "goal of the directive", 2, 1)]
+    #[case("# capacity\nx1 <= 4 // of x1\nz = x1 -> mux", "goal", 3, 7)]
+    fn test_locate_error(
+        #[case] input: &str,
+        #[case] expected: &str,
+        #[case] line: usize,
+        #[case] column: usize,
+    ) {
+        let err = input.parse::<Task>().unwrap_err();
+
+        assert_eq!(
+            err,
+            SimplexParseErr {
+                expected: expected.to_owned(),
+                line,
+                column
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("z = x1 -> maximize", Goal::Maximize)]
+    #[case("z = x1 -> Minimize", Goal::Minimize)]
+    #[case("maximize z = x1", Goal::Maximize)]
+    #[case("MIN z = x1", Goal::Minimize)]
+    #[case("minimize z = x1 -> min", Goal::Minimize)]
+    #[case("goal: maximize\nz = x1", Goal::Maximize)]
+    fn test_goal_keywords(#[case] input: &str, #[case] goal: Goal) {
+        assert_eq!(
+            target_fn::<nom::error::Error<&str>>().parse(input),
+            Ok((
+                "",
+                TargetFn {
+                    goal,
+                    terms: vec![Term {
+                        coef: 1.into(),
+                        index: 1
+                    }],
+                    value: Default::default()
+                }
+            ))
+        );
+    }
+
+    #[rstest]
+    fn test_leading_goal() {
+        assert_eq!(
+            "x1 <= 4\nmaximize z = x1".parse::<Task>(),
+            "x1 <= 4\nz = x1 -> max".parse::<Task>()
+        );
+        assert!(target_fn::<nom::error::Error<&str>>()
+            .parse("maximize z = x1 -> min")
+            .is_err());
+    }
+
+    #[rstest]
+    fn test_goal_directive_conflict() {
+        assert!(target_fn::<nom::error::Error<&str>>()
+            .parse("goal: min\nz = x1 -> max")
+            .is_err());
+        assert!("x1 <= 1\nz = x1 -> max\ngoal: min".parse::<Task>().is_err());
+        assert!("x1 <= 1\nz = x1".parse::<Task>().is_err());
+    }
+
     #[rstest]
     #[case("x1 + 2x2 == 3", Restriction {
         relation: Relation::Equal,
@@ -357,7 +1619,8 @@ mod tests {
             coef: 2.into(),
             index: 2
         }],
-        value: 3.into()
+        value: 3.into(),
+        section: None
     })]
     fn test_restriction(#[case] input: &str, #[case] res: Restriction) {
         assert_eq!(
@@ -370,6 +1633,9 @@ mod tests {
     #[case("==", Relation::Equal)]
     #[case("<=", Relation::Less)]
     #[case(">=", Relation::Greater)]
+    #[case("=", Relation::Equal)]
+    #[case("≤", Relation::Less)]
+    #[case("≥", Relation::Greater)]
     #[should_panic]
     #[case("NaN", Relation::Equal)]
     fn test_relation(#[case] rel_str: &str, #[case] rel: Relation) {
@@ -378,4 +1644,254 @@ mod tests {
             Ok(("", rel))
         );
     }
+
+    #[rstest]
+    #[case(DEFAULT_INDEX_RATIO, Err(SimplexBuildError::IndexTooLarge { index: 1000000, limit: 300 }))]
+    #[case(1_000_000, Ok(()))]
+    fn test_check_indices(#[case] ratio: u64, #[case] res: Result<(), SimplexBuildError>) {
+        let task: Task = "x1 + x1000000 <= 3\nz = x1 -> max".parse().unwrap();
+
+        assert_eq!(task.check_indices(ratio), res);
+    }
+
+    #[rstest]
+    #[case(1, "x1 + x3 <= 3\nz = x1 -> max", Err(SimplexBuildError::MissingIndices(vec![2])))]
+    #[case(1, "x1 + x3 <= 3\nz = x1 + x2 -> max", Ok(()))]
+    #[case(1, "x1 + x3 <= 3\nz = x1 -> max\nx2 <= 4", Ok(()))]
+    #[case(0, "x0 + x2 <= 3\nz = x0 -> max", Err(SimplexBuildError::MissingIndices(vec![1])))]
+    fn test_check_contiguous(
+        #[case] index_base: u64,
+        #[case] input: &str,
+        #[case] res: Result<(), SimplexBuildError>,
+    ) {
+        let task = Task::parse_with(input, ParserConfig { index_base }).unwrap();
+
+        assert_eq!(task.check_contiguous(index_base), res);
+    }
+
+    #[rstest]
+    fn test_display_round_trip() {
+        let input = "x1 + -2x2 <= 4\n3x1 + x2 == 5\nz = x1 + -x2 -> min\nsolve using taxes\n";
+        let task: Task = input.parse().unwrap();
+
+        assert_eq!(task.to_string(), input);
+    }
+
+    #[rstest]
+    fn test_piecewise() {
+        assert_eq!(
+            piecewise::<nom::error::Error<&str>>().parse("pwl x1: (0,0) (5, 10) (10,25)"),
+            Ok((
+                "",
+                PiecewiseTerm {
+                    index: 1,
+                    breakpoints: vec![
+                        (0.into(), 0.into()),
+                        (5.into(), 10.into()),
+                        (10.into(), 25.into())
+                    ]
+                }
+            ))
+        );
+    }
+
+    #[rstest]
+    #[case("soft x1 >= 1 penalty 3\nz = x2 -> min", Relation::Greater, 1, 3)]
+    #[case("soft c1: x1 + x2 <= 4 penalty 3\nz = x2 -> max", Relation::Less, -1, -3)]
+    fn test_soft_restriction(
+        #[case] input: &str,
+        #[case] relation: Relation,
+        #[case] deviation: i64,
+        #[case] penalty: i64,
+    ) {
+        let task: Task = input.parse().unwrap();
+        let soft = task
+            .restrictions
+            .iter()
+            .find(|x| x.relation == relation)
+            .unwrap();
+
+        assert_eq!(
+            soft.terms.last(),
+            Some(&Term {
+                coef: deviation.into(),
+                index: 3
+            })
+        );
+        assert_eq!(
+            task.target_fn.terms.last(),
+            Some(&Term {
+                coef: penalty.into(),
+                index: 3
+            })
+        );
+    }
+
+    #[rstest]
+    #[case("\u{feff}x1 <= 4\nz = x1 -> max")]
+    #[case("\n  \r\n\tx1 <= 4\nz = x1 -> max")]
+    fn test_leading_noise(#[case] input: &str) {
+        assert_eq!(
+            input.parse::<Task>(),
+            "x1 <= 4\nz = x1 -> max".parse::<Task>()
+        );
+    }
+
+    #[rstest]
+    #[case("x3 >= 0", 1)]
+    #[case("-2x3 <= 0", 1)]
+    #[case("x3 >= 2", 2)]
+    #[case("x3 <= 0", 2)]
+    fn test_nonnegativity_dropped(#[case] bound: &str, #[case] rows: usize) {
+        let task: Task = format!("x1 + x3 <= 4\n{bound}\nz = x1 + x3 -> max")
+            .parse()
+            .unwrap();
+
+        assert_eq!(task.restrictions.len(), rows);
+    }
+
+    #[test]
+    fn test_nonnegativity_of_free_variable() {
+        let input = "x1 + x2 <= 4\nx1 >= 0\nz = x1 -> min\nx1 free\nsolve using taxes";
+        let task: Task = input.parse().unwrap();
+
+        // `x1 >= 0` is no longer implied, so the row keeps the minimum at 0
+        assert_eq!(task.restrictions.len(), 2);
+        let solution = solve_task(task).unwrap();
+        assert_eq!(solution.objective_rational(), Some(Rational64::from(0)));
+    }
+
+    #[rstest]
+    fn test_comments() {
+        let input = "# a small plan\n\
+                     x1 + x2 <= 4 # hours\n\
+                     // the second machine\n\
+                     x1 <= 3    // hours\n\
+                     z = 2x1 + x2 -> max # profit\n\
+                     # the end";
+        let task: Task = input.parse().unwrap();
+
+        assert_eq!(
+            task,
+            "x1 + x2 <= 4\nx1 <= 3\nz = 2x1 + x2 -> max"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[rstest]
+    fn test_unicode_relations() {
+        let task: Task = "x1 + x2 ≤ 4\nx1 - x2 ≥ -1\n2x1 = 3\nz = x1 -> max\nx2 ≤ 2"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            task,
+            "x1 + x2 <= 4\nx1 - x2 >= -1\n2x1 == 3\nz = x1 -> max\nx2 <= 2"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("z = x1 + 2x2 -> max\nx1 + x2 <= 4\nx2 <= 3")]
+    #[case("goal: max\nz = x1 + 2x2\nx1 + x2 <= 4\nx2 <= 3")]
+    fn test_objective_first(#[case] input: &str) {
+        let task: Task = input.parse().unwrap();
+
+        assert_eq!(
+            task,
+            "x1 + x2 <= 4\nx2 <= 3\nz = x1 + 2x2 -> max"
+                .parse()
+                .unwrap()
+        );
+    }
+
+    #[rstest]
+    #[case("x1 + x2 <= 4; x2 <= 3; z = x1 + x2 -> max")]
+    #[case("x1 + x2 <= 4 ;x2 <= 3\nz = x1 + x2 -> max")]
+    fn test_semicolons(#[case] input: &str) {
+        let task: Task = input.parse().unwrap();
+
+        assert_eq!(
+            task,
+            "x1 + x2 <= 4\nx2 <= 3\nz = x1 + x2 -> max".parse().unwrap()
+        );
+    }
+
+    #[rstest]
+    fn test_allocation() {
+        let task: Task = "simplex x1 x[2]  x3\nz = x1 -> max".parse().unwrap();
+
+        assert_eq!(task, "x1 + x2 + x3 == 1\nz = x1 -> max".parse().unwrap());
+    }
+
+    #[rstest]
+    #[case("x1 + x2 \\\n+ x3 <= 4\nz = x1 -> max")]
+    #[case("x1 + x2 \\\r\n+ x3 <= 4\r\nz = x1 -> max")]
+    fn test_continuation(#[case] input: &str) {
+        let task: Task = input.parse().unwrap();
+
+        assert_eq!(task, "x1 + x2 + x3 <= 4\nz = x1 -> max".parse().unwrap());
+    }
+
+    #[rstest]
+    fn test_piecewise_expansion() {
+        let task: Task = "x1 + x2 <= 10\nz = x2 -> max\npwl x1: (0,0) (5,10)"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            task.restrictions[1..],
+            [
+                Restriction {
+                    relation: Relation::Equal,
+                    terms: vec![
+                        Term {
+                            coef: 1.into(),
+                            index: 1
+                        },
+                        Term {
+                            coef: 0.into(),
+                            index: 3
+                        },
+                        Term {
+                            coef: (-5).into(),
+                            index: 4
+                        }
+                    ],
+                    value: 0.into(),
+                    section: None
+                },
+                Restriction {
+                    relation: Relation::Equal,
+                    terms: vec![
+                        Term {
+                            coef: 1.into(),
+                            index: 3
+                        },
+                        Term {
+                            coef: 1.into(),
+                            index: 4
+                        }
+                    ],
+                    value: 1.into(),
+                    section: None
+                }
+            ]
+        );
+        assert_eq!(
+            task.target_fn.terms[1..],
+            [
+                Term {
+                    coef: 0.into(),
+                    index: 3
+                },
+                Term {
+                    coef: 10.into(),
+                    index: 4
+                }
+            ]
+        );
+    }
 }
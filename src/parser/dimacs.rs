@@ -0,0 +1,277 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{i64, line_ending, multispace0, not_line_ending, space1},
+    combinator::{all_consuming, map},
+    error::{context, ContextError, ParseError},
+    multi::separated_list0,
+    sequence::{delimited, preceded, tuple},
+    Parser,
+};
+use num::{Rational64, Zero};
+
+use super::{decimal, locate_error, Goal, Method, Relation, Restriction, TargetFn, Task, Term};
+use crate::errors::SimplexParseErr;
+
+/// Min-cost flow problem read from the DIMACS format and translated into a [`Task`]
+#[derive(Debug, PartialEq)]
+pub struct Network {
+    pub task: Task,
+    /// `(from, to)` nodes of the arc whose flow is `x{i + 1}`
+    pub arcs: Vec<(u64, u64)>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Arc {
+    from: u64,
+    to: u64,
+    low: u64,
+    capacity: u64,
+    cost: i64,
+}
+
+#[derive(Debug, PartialEq)]
+enum Line {
+    Comment,
+    Problem,
+    Node(u64, i64),
+    Arc(Arc),
+}
+
+/// 'c' <anything>
+fn comment<'a, E>() -> impl Parser<&'a str, Line, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "comment",
+        map(preceded(tag("c"), not_line_ending), |_| Line::Comment),
+    )
+}
+
+/// 'p' +'min' +<nodes> +<arcs>
+fn problem<'a, E>() -> impl Parser<&'a str, Line, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "problem",
+        map(
+            tuple((
+                tag("p"),
+                preceded(space1, tag("min")),
+                preceded(space1, decimal),
+                preceded(space1, decimal),
+            )),
+            |_| Line::Problem,
+        ),
+    )
+}
+
+/// 'n' +<id> +<supply>
+fn node<'a, E>() -> impl Parser<&'a str, Line, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "node",
+        map(
+            tuple((tag("n"), preceded(space1, decimal), preceded(space1, i64))),
+            |(_, id, supply)| Line::Node(id, supply),
+        ),
+    )
+}
+
+/// 'a' +<from> +<to> +<low> +<capacity> +<cost>
+fn arc<'a, E>() -> impl Parser<&'a str, Line, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "arc",
+        map(
+            tuple((
+                tag("a"),
+                preceded(space1, decimal),
+                preceded(space1, decimal),
+                preceded(space1, decimal),
+                preceded(space1, decimal),
+                preceded(space1, i64),
+            )),
+            |(_, from, to, low, capacity, cost)| {
+                Line::Arc(Arc {
+                    from,
+                    to,
+                    low,
+                    capacity,
+                    cost,
+                })
+            },
+        ),
+    )
+}
+
+impl Network {
+    fn parse<'a, E>() -> impl Parser<&'a str, Network, E>
+    where
+        E: ParseError<&'a str> + ContextError<&'a str>,
+    {
+        context(
+            "network",
+            map(
+                all_consuming(delimited(
+                    multispace0,
+                    separated_list0(line_ending, alt((comment(), problem(), node(), arc()))),
+                    multispace0,
+                )),
+                Network::from_lines,
+            ),
+        )
+    }
+
+    /// Every arc becomes a variable bounded by its capacity, every node a flow conservation
+    /// equality `outflow - inflow == supply`, and the total cost is minimized.
+    fn from_lines(lines: Vec<Line>) -> Network {
+        let mut supplies = BTreeMap::new();
+        let mut arcs = vec![];
+        for line in lines {
+            match line {
+                Line::Node(id, supply) => {
+                    supplies.insert(id, supply);
+                }
+                Line::Arc(arc) => arcs.push(arc),
+                Line::Comment | Line::Problem => (),
+            }
+        }
+
+        let mut balance = BTreeMap::<u64, Vec<Term>>::new();
+        let mut restrictions = vec![];
+        for (i, arc) in arcs.iter().enumerate() {
+            let index = i as u64 + 1;
+
+            balance.entry(arc.from).or_default().push(Term {
+                coef: 1.into(),
+                index,
+            });
+            balance.entry(arc.to).or_default().push(Term {
+                coef: (-1).into(),
+                index,
+            });
+            restrictions.push(Restriction {
+                relation: Relation::Less,
+                terms: vec![Term {
+                    coef: 1.into(),
+                    index,
+                }],
+                value: (arc.capacity as i64).into(),
+                section: None,
+            });
+            if arc.low > 0 {
+                restrictions.push(Restriction {
+                    relation: Relation::Greater,
+                    terms: vec![Term {
+                        coef: 1.into(),
+                        index,
+                    }],
+                    value: (arc.low as i64).into(),
+                    section: None,
+                });
+            }
+        }
+        restrictions.extend(balance.into_iter().map(|(node, terms)| Restriction {
+            relation: Relation::Equal,
+            terms,
+            value: supplies.get(&node).copied().unwrap_or(0).into(),
+            section: None,
+        }));
+
+        let target_fn = TargetFn {
+            goal: Goal::Minimize,
+            terms: arcs
+                .iter()
+                .enumerate()
+                .filter(|(_, arc)| arc.cost != 0)
+                .map(|(i, arc)| Term {
+                    coef: arc.cost.into(),
+                    index: i as u64 + 1,
+                })
+                .collect(),
+            value: Rational64::zero(),
+        };
+
+        Network {
+            task: Task {
+                restrictions,
+                target_fn,
+                bounds: vec![],
+                method: Method::Taxes,
+            },
+            arcs: arcs.into_iter().map(|x| (x.from, x.to)).collect(),
+        }
+    }
+}
+
+impl FromStr for Network {
+    type Err = SimplexParseErr;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Network::parse::<nom::error::VerboseError<&str>>()
+            .parse(s)
+            .map(|x| x.1)
+            .map_err(|x| {
+                let err = x.map(|y| nom::error::VerboseError {
+                    errors: y
+                        .errors
+                        .into_iter()
+                        .map(|z| (z.0.to_owned(), z.1))
+                        .collect(),
+                });
+                locate_error(s, err)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Rational64;
+
+    use crate::{
+        parser::dimacs::Network,
+        simplex::SimplexSolver,
+        task::{SimplexTask, Taxes},
+        tax_numbers::Tax,
+    };
+
+    const NETWORK: &str = "c tiny network from the DIMACS description
+p min 4 5
+n 1 4
+n 4 -4
+a 1 2 0 4 2
+a 1 3 0 2 2
+a 2 3 0 2 1
+a 2 4 0 3 3
+a 3 4 0 5 1
+";
+
+    #[test]
+    fn test_parse_network() {
+        let network: Network = NETWORK.parse().unwrap();
+
+        assert_eq!(network.arcs, vec![(1, 2), (1, 3), (2, 3), (2, 4), (3, 4)]);
+        // a capacity per arc and a balance per node
+        assert_eq!(network.task.restrictions.len(), 5 + 4);
+    }
+
+    #[test]
+    fn test_min_cost_flow() {
+        let network: Network = NETWORK.parse().unwrap();
+        let task: SimplexTask<Tax<Rational64>> = network.task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        // 2 units along 1 -> 3 -> 4 and 2 units along 1 -> 2 -> 3 -> 4
+        assert!(solution.to_string().contains("Optimal z is: 14\n"));
+    }
+}
@@ -0,0 +1,558 @@
+use std::collections::HashMap;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, tag_no_case, take_while},
+    character::complete::{char, multispace0, multispace1, one_of, satisfy},
+    combinator::{all_consuming, map, opt, recognize, verify},
+    error::{context, ContextError, ParseError},
+    multi::{many0, many1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
+    Parser,
+};
+use num::{Rational64, Zero};
+
+use super::{
+    coefficient, locate_error, ws, Bound, BoundKind, Goal, Method, Relation, Restriction, TargetFn,
+    Task, Term,
+};
+use crate::errors::SimplexParseErr;
+
+/// Words starting a section or standing for a number, which can't name a variable
+const KEYWORDS: &[&str] = &[
+    "maximize", "maximum", "max", "minimize", "minimum", "min", "subject", "such", "st", "s.t.",
+    "bounds", "bound", "free", "end", "inf", "infinity",
+];
+
+/// `coef * name`, or a constant without a name
+type LpTerm<'a> = (Rational64, Option<&'a str>);
+
+/// A limit of a variable, `None` for an infinite one
+type Limit = Option<Rational64>;
+
+/// A line of the `Bounds` section, `lower` holding `Some(None)` for a free variable
+#[derive(Debug, PartialEq)]
+struct LpBound<'a> {
+    name: &'a str,
+    lower: Option<Limit>,
+    upper: Option<Limit>,
+}
+
+#[derive(Debug, PartialEq)]
+struct LpModel<'a> {
+    goal: Goal,
+    objective: Vec<LpTerm<'a>>,
+    constraints: Vec<(Vec<LpTerm<'a>>, Relation, Rational64)>,
+    bounds: Vec<LpBound<'a>>,
+}
+
+/// <letter or _>(<letter, digit or _.[]>)*, except for [`KEYWORDS`]
+fn name<'a, E>() -> impl Parser<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "name",
+        verify(
+            recognize(pair(
+                satisfy(|c| c.is_alphabetic() || c == '_'),
+                take_while(|c: char| c.is_alphanumeric() || "_.[]".contains(c)),
+            )),
+            |x: &str| !KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(x)),
+        ),
+    )
+}
+
+/// <name> *':'
+fn label<'a, E>() -> impl Parser<&'a str, &'a str, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    terminated(name(), ws(char(':')))
+}
+
+/// [<coefficient>] *<name> | <coefficient>
+fn unsigned_term<'a, E>() -> impl Parser<&'a str, LpTerm<'a>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    alt((
+        pair(opt(terminated(coefficient(), multispace0)), name())
+            .map(|(coef, name)| (coef.unwrap_or(Rational64::from_integer(1)), Some(name))),
+        coefficient().map(|coef| (coef, None)),
+    ))
+}
+
+/// Terms joined by `+` and `-`, which may span several lines
+fn expression<'a, E>() -> impl Parser<&'a str, Vec<LpTerm<'a>>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    let signed = |s| {
+        let (s, sign) = ws(one_of("+-")).parse(s)?;
+        let (s, (coef, name)) = unsigned_term().parse(s)?;
+        Ok((s, (if sign == '-' { -coef } else { coef }, name)))
+    };
+
+    context(
+        "expression",
+        map(
+            pair(alt((signed, unsigned_term())), many0(signed)),
+            |(first, mut rest)| {
+                rest.insert(0, first);
+                rest
+            },
+        ),
+    )
+}
+
+/// '<=' | '=<' | '<' | '>=' | '=>' | '>' | '='
+fn relation<'a, E>() -> impl Parser<&'a str, Relation, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "relation",
+        alt((
+            alt((tag("<="), tag("=<"), tag("<"))).map(|_| Relation::Less),
+            alt((tag(">="), tag("=>"), tag(">"))).map(|_| Relation::Greater),
+            tag("=").map(|_| Relation::Equal),
+        )),
+    )
+}
+
+/// [+-]<coefficient>
+fn number<'a, E>() -> impl Parser<&'a str, Rational64, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    map(
+        pair(opt(terminated(one_of("+-"), multispace0)), coefficient()),
+        |(sign, x)| if sign == Some('-') { -x } else { x },
+    )
+}
+
+/// [+-]('inf' | 'infinity') | <number>
+fn limit<'a, E>() -> impl Parser<&'a str, Limit, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    alt((
+        map(
+            pair(
+                opt(terminated(one_of("+-"), multispace0)),
+                alt((tag_no_case("infinity"), tag_no_case("inf"))),
+            ),
+            |_| None,
+        ),
+        map(number(), Some),
+    ))
+}
+
+/// ('maximize' | 'minimize' | ...) [<label>] <expression>
+fn objective<'a, E>() -> impl Parser<&'a str, (Goal, Vec<LpTerm<'a>>), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "objective",
+        pair(
+            ws(alt((
+                alt((
+                    tag_no_case("maximize"),
+                    tag_no_case("maximum"),
+                    tag_no_case("max"),
+                ))
+                .map(|_| Goal::Maximize),
+                alt((
+                    tag_no_case("minimize"),
+                    tag_no_case("minimum"),
+                    tag_no_case("min"),
+                ))
+                .map(|_| Goal::Minimize),
+            ))),
+            preceded(opt(label()), expression()),
+        ),
+    )
+}
+
+/// [<label>] <expression> *<relation> *<number>
+fn constraint<'a, E>() -> impl Parser<&'a str, (Vec<LpTerm<'a>>, Relation, Rational64), E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    context(
+        "constraint",
+        tuple((
+            preceded(opt(label()), expression()),
+            ws(relation()),
+            number(),
+        )),
+    )
+}
+
+/// <name> +'free' | <limit> *<relation> *<name> *<relation> *<limit> | <limit> *<relation> *<name>
+/// | <name> *<relation> *<limit>
+fn bound<'a, E>() -> impl Parser<&'a str, LpBound<'a>, E>
+where
+    E: ParseError<&'a str> + ContextError<&'a str>,
+{
+    // `limit <= x` bounds `x` from below just like `x >= limit`
+    let flipped = |relation| match relation {
+        Relation::Less => Relation::Greater,
+        Relation::Greater => Relation::Less,
+        Relation::Equal => Relation::Equal,
+    };
+    let single = |name, relation, limit| match relation {
+        Relation::Less => LpBound {
+            name,
+            lower: None,
+            upper: Some(limit),
+        },
+        Relation::Greater => LpBound {
+            name,
+            lower: Some(limit),
+            upper: None,
+        },
+        Relation::Equal => LpBound {
+            name,
+            lower: Some(limit),
+            upper: Some(limit),
+        },
+    };
+
+    context(
+        "bound",
+        alt((
+            map(
+                separated_pair(name(), multispace1, tag_no_case("free")),
+                |(name, _)| LpBound {
+                    name,
+                    lower: Some(None),
+                    upper: None,
+                },
+            ),
+            map(
+                tuple((limit(), ws(tag("<=")), name(), ws(tag("<=")), limit())),
+                |(lower, _, name, _, upper)| LpBound {
+                    name,
+                    lower: Some(lower),
+                    upper: Some(upper),
+                },
+            ),
+            map(
+                tuple((limit(), ws(relation()), name())),
+                move |(x, r, name)| single(name, flipped(r), x),
+            ),
+            map(
+                tuple((name(), ws(relation()), limit())),
+                move |(name, r, x)| single(name, r, x),
+            ),
+        )),
+    )
+}
+
+impl<'a> LpModel<'a> {
+    fn parse<E>() -> impl Parser<&'a str, LpModel<'a>, E>
+    where
+        E: ParseError<&'a str> + ContextError<&'a str>,
+    {
+        let subject_to = alt((
+            recognize(tuple((
+                tag_no_case("subject"),
+                multispace1,
+                tag_no_case("to"),
+            ))),
+            recognize(tuple((
+                tag_no_case("such"),
+                multispace1,
+                tag_no_case("that"),
+            ))),
+            tag_no_case("s.t."),
+            tag_no_case("st"),
+        ));
+        let bounds = preceded(
+            ws(alt((tag_no_case("bounds"), tag_no_case("bound")))),
+            many0(ws(bound())),
+        );
+
+        context(
+            "lp",
+            map(
+                all_consuming(tuple((
+                    objective(),
+                    preceded(ws(subject_to), many1(ws(constraint()))),
+                    opt(bounds),
+                    delimited(multispace0, context("end", tag_no_case("end")), multispace0),
+                ))),
+                |((goal, objective), constraints, bounds, _)| LpModel {
+                    goal,
+                    objective,
+                    constraints,
+                    bounds: bounds.unwrap_or_default(),
+                },
+            ),
+        )
+    }
+
+    /// Gives every `x<n>` the index `n`, so that a model written with `x1, x2, ...` keeps its
+    /// indices, and numbers the other names after the largest of them in the order they appear
+    fn indices(&self) -> HashMap<&'a str, u64> {
+        let names = self
+            .objective
+            .iter()
+            .chain(self.constraints.iter().flat_map(|x| &x.0))
+            .filter_map(|x| x.1)
+            .chain(self.bounds.iter().map(|x| x.name));
+        let numbered = |name: &str| {
+            name.strip_prefix('x')
+                .filter(|x| !x.starts_with('0') && x.chars().all(|c| c.is_ascii_digit()))
+                .and_then(|x| x.parse::<u64>().ok())
+        };
+
+        let mut indices: HashMap<_, _> = names
+            .clone()
+            .filter_map(|x| Some((x, numbered(x)?)))
+            .collect();
+        let mut next = indices.values().max().copied().unwrap_or_default();
+        for name in names {
+            indices.entry(name).or_insert_with(|| {
+                next += 1;
+                next
+            });
+        }
+        indices
+    }
+
+    fn into_task(self) -> Task {
+        let indices = self.indices();
+        let index = |name: &'a str| indices[name];
+        // constants are summed up instead of becoming terms
+        let split = |terms: Vec<LpTerm<'a>>| {
+            let mut constant = Rational64::zero();
+            let mut out = vec![];
+            for (coef, name) in terms {
+                match name {
+                    Some(name) => out.push(Term {
+                        coef,
+                        index: index(name),
+                    }),
+                    None => constant += coef,
+                }
+            }
+            (out, constant)
+        };
+
+        let (terms, value) = split(self.objective);
+        let target_fn = TargetFn {
+            goal: self.goal,
+            terms,
+            value,
+        };
+        let mut restrictions = vec![];
+        for (terms, relation, rhs) in self.constraints {
+            let (terms, constant) = split(terms);
+            restrictions.push(Restriction {
+                relation,
+                terms,
+                value: rhs - constant,
+                section: None,
+            });
+        }
+
+        // every variable is nonnegative unless its lower bound says otherwise
+        let mut bounds = vec![];
+        for bound in self.bounds {
+            let index = index(bound.name);
+            let mut kinds = vec![];
+            match bound.lower {
+                Some(None) => kinds.push(BoundKind::Free),
+                Some(Some(x)) if !x.is_zero() => kinds.push(BoundKind::Lower(x)),
+                _ => (),
+            }
+            if let Some(Some(x)) = bound.upper {
+                kinds.push(BoundKind::Upper(x));
+            }
+            bounds.extend(kinds.into_iter().map(|kind| Bound { index, kind }));
+        }
+
+        let mut task = Task {
+            restrictions,
+            target_fn,
+            bounds,
+            method: Method::Simple,
+        };
+        task.drop_nonnegativity();
+
+        // the simple method needs a slack in every row to start from
+        let simple = task
+            .restrictions
+            .iter()
+            .all(|x| x.relation == Relation::Less && x.value >= Rational64::zero())
+            && !task
+                .bounds
+                .iter()
+                .any(|x| matches!(x.kind, BoundKind::Lower(_)));
+        if !simple {
+            task.method = Method::Taxes;
+        }
+
+        task
+    }
+}
+
+/// Replaces `\` comments with spaces, keeping every offset into the result valid in `s`
+fn blank_comments(s: &str) -> String {
+    s.split_inclusive('\n')
+        .map(|line| match line.find('\\') {
+            Some(start) => {
+                let end = line.trim_end_matches(['\r', '\n']).len();
+                format!(
+                    "{}{}{}",
+                    &line[..start],
+                    " ".repeat(end - start),
+                    &line[end..]
+                )
+            }
+            None => line.to_owned(),
+        })
+        .collect()
+}
+
+/// Reads a model in the CPLEX LP format:
+///
+/// ```text
+/// \ comments start with a backslash
+/// Maximize
+///  obj: 3 x + 5 y
+/// Subject To
+///  c1: x <= 4
+///  c2: 3 x + 2 y <= 18
+/// Bounds
+///  y <= 6
+/// End
+/// ```
+///
+/// A variable named `x<n>` gets the index `n`, the others are numbered after the largest such
+/// index in the order they first appear, and labels are dropped. Only the
+/// objective, constraint and bound sections are supported, not `General` or `Binary` ones.
+pub fn parse_lp(input: &str) -> Result<Task, SimplexParseErr> {
+    let text = blank_comments(input);
+    let result = LpModel::parse::<nom::error::VerboseError<&str>>().parse(&text);
+
+    result.map(|x| x.1.into_task()).map_err(|x| {
+        let err = x.map(|y| nom::error::VerboseError {
+            errors: y
+                .errors
+                .into_iter()
+                .map(|z| (z.0.to_owned(), z.1))
+                .collect(),
+        });
+        locate_error(&text, err)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use num::Rational64;
+
+    use crate::{
+        parser::{lp_format::parse_lp, Bound, BoundKind, Method, Task},
+        simplex::SimplexSolver,
+        task::{Simple, SimplexTask},
+    };
+
+    const LP: &str = "\\ the Wyndor Glass problem
+Maximize
+ obj: 3 x1 + 5 x2
+Subject To
+ plant1: x1 <= 4
+ plant2: 2 x2
+   <= 12
+ plant3: 3 x1 + 2 x2 <= 18
+Bounds
+ x1 >= 0
+End
+";
+
+    #[test]
+    fn test_parse_lp() {
+        let task = parse_lp(LP).unwrap();
+        let native: Task = "x1 <= 4\n2x2 <= 12\n3x1 + 2x2 <= 18\nz = 3x1 + 5x2 -> max"
+            .parse()
+            .unwrap();
+
+        assert_eq!(task, native);
+        // and back through the crate's own format
+        assert_eq!(task.to_string().parse::<Task>().unwrap(), task);
+
+        let task: SimplexTask<Rational64> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        assert!(solver
+            .solve()
+            .unwrap()
+            .to_string()
+            .contains("Optimal z is: 36\n"));
+    }
+
+    #[test]
+    fn test_lp_bounds() {
+        let task = parse_lp(
+            "minimize\n cost: a - b + 2\nst\n a + b >= 1\nbounds\n-inf <= a <= 3\n-2 <= b\nc free\nend",
+        )
+        .unwrap();
+
+        assert_eq!(task.target_fn.value, 2.into());
+        assert_eq!(
+            task.bounds,
+            vec![
+                Bound {
+                    index: 1,
+                    kind: BoundKind::Free,
+                },
+                Bound {
+                    index: 1,
+                    kind: BoundKind::Upper(3.into()),
+                },
+                Bound {
+                    index: 2,
+                    kind: BoundKind::Lower((-2).into()),
+                },
+                Bound {
+                    index: 3,
+                    kind: BoundKind::Free,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lp_indices() {
+        let task =
+            parse_lp("max\n obj: 5 x2 + 3 x1 + y\nst\n c1: x1 + x2 + y <= 4\n c2: x1 >= 0\nend")
+                .unwrap();
+        let native: Task = "x1 + x2 + x3 <= 4\nz = 5x2 + 3x1 + x3 -> max"
+            .parse()
+            .unwrap();
+
+        // `y` comes after the largest `x<n>`, and the dropped `x1 >= 0` row leaves the task simple
+        assert_eq!(task, native);
+        assert_eq!(task.method, Method::Simple);
+    }
+
+    #[test]
+    fn test_lp_free_nonnegativity() {
+        let task = parse_lp("min\n obj: x\nst\n c1: x + y <= 4\n c2: x >= 0\nbounds\nx free\nend")
+            .unwrap();
+
+        // `x` may go negative, so the row is what keeps it at zero
+        assert_eq!(task.restrictions.len(), 2);
+    }
+
+    #[test]
+    fn test_lp_error() {
+        let err = parse_lp("Maximize\n obj: x\nSubject To\n c1: x <= 4\n").unwrap_err();
+
+        assert_eq!((err.expected.as_str(), err.line), ("end", 5));
+    }
+}
@@ -1,36 +1,842 @@
 extern crate ndarray;
 
-use ndarray::{prelude::*, LinalgScalar};
-use num::{traits::NumAssign, Num, Zero};
-use std::{fmt::{Display}, ops::Div};
+use ndarray::{concatenate, prelude::*};
+use num::{traits::NumAssign, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, Zero};
+use std::{
+    collections::{btree_map, BTreeMap, HashMap},
+    fmt::Display,
+    fs::{read_to_string, write},
+    io,
+    ops::Sub,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
-use crate::{errors::SimplexMethodError, parser::Goal};
+use crate::{
+    errors::{OptimalityViolation, SimplexBuildError, SimplexMethodError},
+    parser::{Goal, Method},
+    tax_numbers::Tax,
+};
 
+/// What is printed while and after solving
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OutputStyle {
+    #[default]
+    SolutionOnly,
+    Tableaux,
+    Both,
+    /// The initial and the optimal tableau with basis labels, then the solution
+    Pretty,
+}
+
+impl OutputStyle {
+    pub fn tableaux(self) -> bool {
+        matches!(self, OutputStyle::Tableaux | OutputStyle::Both)
+    }
+
+    pub fn solution(self) -> bool {
+        matches!(
+            self,
+            OutputStyle::SolutionOnly | OutputStyle::Both | OutputStyle::Pretty
+        )
+    }
+}
+
+impl FromStr for OutputStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "solution" => Ok(OutputStyle::SolutionOnly),
+            "tableaux" => Ok(OutputStyle::Tableaux),
+            "both" => Ok(OutputStyle::Both),
+            "pretty" => Ok(OutputStyle::Pretty),
+            _ => Err(format!(
+                "unknown output style `{s}`, expected solution, tableaux, both or pretty"
+            )),
+        }
+    }
+}
+
+/// How the solution is printed
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Markdown,
+    /// A [`SolutionReport`] for other programs to read
+    #[cfg(feature = "serde")]
+    Json,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "md" => Ok(OutputFormat::Markdown),
+            #[cfg(feature = "serde")]
+            "json" => Ok(OutputFormat::Json),
+            _ => {
+                let expected = if cfg!(feature = "serde") {
+                    "text, md or json"
+                } else {
+                    "text or md"
+                };
+                Err(format!("unknown output format `{s}`, expected {expected}"))
+            }
+        }
+    }
+}
+
+/// Sees the tableau before every pivot and once more at the optimum
+pub trait SimplexObserver<N> {
+    fn on_iteration(&mut self, tableau: ArrayView2<'_, N>, basis: ArrayView1<'_, usize>);
+}
+
+/// Prints every tableau to stdout followed by its basis, as [`OutputStyle::Tableaux`] does
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutObserver;
+
+impl<N: Display> SimplexObserver<N> for StdoutObserver {
+    fn on_iteration(&mut self, tableau: ArrayView2<'_, N>, basis: ArrayView1<'_, usize>) {
+        for row in tableau.outer_iter() {
+            for item in &row {
+                print!("{:<14} ", item.to_string());
+            }
+            println!();
+        }
+        println!("Basic: {basis}");
+    }
+}
+
+/// How the entering and the leaving variable are chosen
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum AntiCycling {
+    /// The column with the largest improvement, ties in the ratio test going to the first row.
+    /// Usually takes fewer iterations, but may cycle on a degenerate task.
+    #[default]
+    Dantzig,
+    /// The first improving column, ties in the ratio test going to the row whose basic variable
+    /// has the lowest index. Never cycles.
+    Bland,
+}
+
+#[derive(Clone)]
 pub struct SimplexSolver<N> {
     _contents: Array2<N>,
     basis: Array1<usize>,
     aim: Goal,
+    slacks: Vec<Option<usize>>,
+    /// Number of the decision variables, the first columns of the tableau
+    variables: usize,
+    /// How much the objective row's free term changes per unit of each right-hand side
+    rhs_weights: Array1<N>,
+    style: OutputStyle,
+    time_limit: Option<Duration>,
+    rule: AntiCycling,
+    /// How many pivots to make before giving up, 100 per column by default
+    max_iterations: Option<usize>,
+    /// Canonicalization the tableau was built with, none if it was given directly
+    method: Option<Method>,
+    /// Columns of the nonnegative and the nonpositive part of every free variable
+    splits: Vec<(usize, usize)>,
+    /// Restriction rows multiplied by -1 to get a nonnegative right-hand side
+    negated: Vec<bool>,
+    /// How many of the last restriction rows are the rows of variable bounds
+    bound_rows: usize,
+    /// Columns of the artificial variables taxes penalize
+    artificials: Vec<usize>,
+    /// Sees every tableau instead of the output style
+    observer: Option<Arc<Mutex<dyn SimplexObserver<N> + Send>>>,
+}
+
+/// What a solution amounts to, for other programs to read. Numbers are written as in
+/// [`Display`], so that fractions stay exact.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SolutionReport {
+    pub objective: String,
+    /// Value of every decision variable by its label
+    pub variables: BTreeMap<String, String>,
+    /// Indices of the basic variables, counted like their labels
+    pub basis: Vec<usize>,
+    pub iterations: usize,
+}
+
+/// A pivot made while solving, with the objective value it led to
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pivot<N> {
+    /// Column of the entering variable
+    pub column: usize,
+    /// Row the leaving variable was basic in
+    pub row: usize,
+    pub value: N,
+    pub objective: N,
 }
 
 pub struct Solution<N> {
     basis_coeffs: Array1<(usize, N)>,
     coefficients: Array1<N>,
+    slacks: Vec<Option<usize>>,
+    variables: usize,
+    /// Restriction rows of the final tableau, the free term being the last column
+    rows: Array2<N>,
+    /// Columns substituted out of the task before solving, with their values
+    fixed: Vec<(usize, N)>,
+    /// Objective value of the starting tableau and after every iteration
+    objectives: Vec<N>,
+    pivots: Vec<Pivot<N>>,
+    /// Columns of the starting basis, if they formed an identity matrix
+    initial_basis: Option<Vec<usize>>,
+    /// Objective row and right-hand sides of the starting tableau
+    initial_z: Array1<N>,
+    initial_b: Array1<N>,
+    aim: Goal,
+    /// Index the labels of the variables start from
+    index_base: usize,
+    method: Option<Method>,
+    splits: Vec<(usize, usize)>,
+    negated: Vec<bool>,
+    bound_rows: usize,
 }
 
-impl<F: Display + Num + NumAssign + Copy> Display for Solution<F> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let xs = self.coefficients.slice(s![..-1]);
-        let free_z = self.coefficients.slice(s![-1]);
+impl<N> Solution<N> {
+    /// Indices of the restrictions that hold with equality at the optimum, i.e. whose slack or
+    /// surplus variable is either non-basic or basic at zero. Equalities are always binding.
+    pub fn binding_constraints(&self) -> Vec<usize>
+    where
+        N: Zero,
+    {
+        self.slacks
+            .iter()
+            .enumerate()
+            .filter(|(_, slack)| match slack {
+                None => true,
+                Some(col) => self
+                    .basis_coeffs
+                    .iter()
+                    .find(|(i, _)| i == col)
+                    .is_none_or(|(_, value)| value.is_zero()),
+            })
+            .map(|(i, _)| i)
+            .collect()
+    }
 
-        let mut optimal_z = *free_z.into_scalar();
-        for &(i, item) in &self.basis_coeffs {
-            optimal_z += xs[i] * item;
+    /// Records the values of variables a presolve substituted out, see
+    /// [`Task::substitute_singletons`](crate::parser::Task::substitute_singletons)
+    pub fn with_fixed(mut self, fixed: Vec<(usize, N)>) -> Self {
+        self.fixed = fixed;
+        self
+    }
+
+    /// Objective value of the starting basis followed by its value after every iteration, the
+    /// last one being the optimum
+    pub fn objective_history(&self) -> &[N] {
+        &self.objectives
+    }
+
+    /// Canonicalization of the task the solution was found with, none if the tableau was given
+    /// directly
+    pub fn method(&self) -> Option<Method> {
+        self.method
+    }
+
+    /// Value of every decision variable keyed by its 1-based index, zero for the non-basic ones.
+    /// A free variable is reported once, as the difference of its two parts.
+    pub fn variable_values(&self) -> HashMap<usize, N>
+    where
+        N: Zero + Clone + Sub<Output = N>,
+    {
+        let mut values: HashMap<_, _> = (1..).zip(self.point(self.variables)).collect();
+        for &(positive, negative) in &self.splits {
+            let negative = values.remove(&(negative + 1)).unwrap_or_else(N::zero);
+            if let Some(value) = values.get_mut(&(positive + 1)) {
+                *value = value.clone() - negative;
+            }
         }
+        values
+    }
 
-        writeln!(f, "Optimal z is: {}", optimal_z)?;
+    /// The pairs of [`Solution::variable_values`] ordered by index
+    pub fn iter(&self) -> btree_map::IntoIter<usize, N>
+    where
+        N: Zero + Clone + Sub<Output = N>,
+    {
+        BTreeMap::from_iter(self.variable_values()).into_iter()
+    }
+
+    /// Labels the variables from `index_base` instead of 1, for a task parsed with the same
+    /// [`ParserConfig`](crate::parser::ParserConfig)
+    pub fn with_index_base(mut self, index_base: usize) -> Self {
+        self.index_base = index_base;
+        self
+    }
+
+    /// Label of the variable in column `column`
+    fn label(&self, column: usize) -> String {
+        format!("x{}", column + self.index_base)
+    }
+
+    /// The final tableau laid out like [`SimplexSolver::tableau`]
+    pub fn tableau(&self) -> Array2<N>
+    where
+        N: Clone,
+    {
+        concatenate![
+            Axis(0),
+            self.rows,
+            self.coefficients.view().insert_axis(Axis(0))
+        ]
+    }
+
+    /// How many pivots the solver made to reach the optimum
+    pub fn iterations(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// The inverse of the optimal basis matrix, read from the final tableau columns of the
+    /// starting basis: those started as the identity matrix and went through the same row
+    /// operations. Row `i` belongs to the basic variable of restriction row `i`.
+    ///
+    /// # Panics
+    /// If the starting basis columns did not form an identity matrix
+    pub fn basis_inverse(&self) -> Array2<N>
+    where
+        N: Clone,
+    {
+        let basis = self
+            .initial_basis
+            .as_ref()
+            .expect("The starting basis is not an identity matrix");
+        self.rows.select(Axis(1), basis)
+    }
+
+    /// Writes the label of the basic variable of every restriction row, one per line, to be
+    /// read back by [`SimplexSolver::load_basis`]
+    pub fn save_basis(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let labels = self
+            .basis_coeffs
+            .iter()
+            .map(|(i, _)| format!("x{}\n", i + 1))
+            .collect::<String>();
+
+        write(path, labels)
+    }
+
+    /// Values of the first `variables` columns, zero for the non-basic ones
+    pub fn point(&self, variables: usize) -> Vec<N>
+    where
+        N: Zero + Clone,
+    {
+        let mut point = vec![N::zero(); variables];
+        for (i, value) in self.basis_coeffs.iter().chain(&self.fixed) {
+            if let Some(x) = point.get_mut(*i) {
+                *x = value.clone();
+            }
+        }
+        point
+    }
+
+    /// [`Solution::point`] as a vector, e.g. to check `A.dot(&x)` against `b`
+    pub fn to_vector(&self, n_vars: usize) -> Array1<N>
+    where
+        N: Zero + Clone,
+    {
+        Array1::from_vec(self.point(n_vars))
+    }
+
+    /// How far each variable could increase with the optimal basis kept, found by the ratio test
+    /// in its column. Basic variables and those no restriction limits report `None`.
+    pub fn variable_ranges(&self) -> Vec<(usize, Option<N>)>
+    where
+        N: Num + Ord + Clone,
+    {
+        let (a, b) = (self.rows.slice(s![.., ..-1]), self.rows.slice(s![.., -1]));
+
+        a.columns()
+            .into_iter()
+            .enumerate()
+            .map(|(j, column)| {
+                if self.basis_coeffs.iter().any(|(i, _)| *i == j) {
+                    return (j, None);
+                }
+
+                let increase = column
+                    .iter()
+                    .zip(b)
+                    .filter(|(x, _)| **x > N::zero())
+                    .map(|(x, y)| y.clone() / x.clone())
+                    .min();
+                (j, increase)
+            })
+            .collect()
+    }
+}
+
+impl<N: Num + Ord + Clone> Solution<N> {
+    /// Non-basic columns with a zero reduced cost, any of which can enter the basis without
+    /// changing the optimum
+    fn tied_columns(&self) -> impl Iterator<Item = usize> + '_ {
+        let reduced_costs = self.coefficients.slice(s![..-1]);
+
+        (0..reduced_costs.len()).filter(move |j| {
+            reduced_costs[*j].is_zero() && self.basis_coeffs.iter().all(|(i, _)| i != j)
+        })
+    }
+
+    /// How far the objective coefficient of every basic decision variable may move with the
+    /// optimal basis kept, as `(column, lower, upper)` bounds on the coefficient. A change of
+    /// the coefficient of the variable basic in row `r` shifts every reduced cost `d_j` by that
+    /// change times `a_rj`, and the basis stays optimal while no reduced cost switches its sign.
+    /// `None` is a side the coefficient may move to without limit.
+    pub fn objective_ranging(&self) -> Vec<(usize, Option<N>, Option<N>)> {
+        let reduced_costs = self.coefficients.slice(s![..-1]);
+        let non_basic: Vec<_> = (0..reduced_costs.len())
+            .filter(|j| self.basis_coeffs.iter().all(|(i, _)| i != j))
+            .collect();
+
+        self.basis_coeffs
+            .iter()
+            .enumerate()
+            .filter(|(_, (column, _))| *column < self.variables)
+            .map(|(row, (column, _))| {
+                let (mut lower, mut upper) = (None::<N>, None::<N>);
+                for &j in &non_basic {
+                    let a = self.rows[[row, j]].clone();
+                    if a.is_zero() {
+                        continue;
+                    }
+                    // the change that brings `d_j` to zero, a bound from below or from above
+                    let limit = N::zero() - reduced_costs[j].clone() / a.clone();
+                    let from_below = match self.aim {
+                        Goal::Maximize => a > N::zero(),
+                        Goal::Minimize => a < N::zero(),
+                    };
+                    if from_below {
+                        lower = Some(lower.map_or(limit.clone(), |x| x.max(limit)));
+                    } else {
+                        upper = Some(upper.map_or(limit.clone(), |x| x.min(limit)));
+                    }
+                }
+                // the starting objective row holds the negated coefficients
+                let coefficient = N::zero() - self.initial_z[*column].clone();
+                let shift = |x: N| coefficient.clone() + x;
+                (*column, lower.map(shift), upper.map(shift))
+            })
+            .collect()
+    }
+
+    /// How far the right-hand side of every restriction may move with the optimal basis kept,
+    /// as `(row, lower, upper)` bounds on it. A change of `b_i` moves the basic values along
+    /// the final column of the starting basis variable of row `i`, and the basis stays feasible
+    /// while none of them turns negative. `None` is a side without a limit.
+    ///
+    /// The bounds are on the right-hand sides as the task gave them, those of rows negated for
+    /// the standard form being negated back, and the rows of variable bounds are left out.
+    ///
+    /// # Panics
+    /// If the starting basis columns did not form an identity matrix
+    pub fn rhs_ranging(&self) -> Vec<(usize, Option<N>, Option<N>)> {
+        let basis = self
+            .initial_basis
+            .as_ref()
+            .expect("The starting basis is not an identity matrix");
+
+        let restrictions = basis.len() - self.bound_rows;
+
+        basis
+            .iter()
+            .take(restrictions)
+            .enumerate()
+            .map(|(i, &column)| {
+                let (mut lower, mut upper) = (None::<N>, None::<N>);
+                for (row, (_, value)) in self.basis_coeffs.iter().enumerate() {
+                    let beta = self.rows[[row, column]].clone();
+                    if beta.is_zero() {
+                        continue;
+                    }
+                    // the change that brings the basic value of `row` to zero
+                    let limit = N::zero() - value.clone() / beta.clone();
+                    if beta > N::zero() {
+                        lower = Some(lower.map_or(limit.clone(), |x| x.max(limit)));
+                    } else {
+                        upper = Some(upper.map_or(limit.clone(), |x| x.min(limit)));
+                    }
+                }
+                let shift = |x: N| self.initial_b[i].clone() + x;
+                let (lower, upper) = (lower.map(shift), upper.map(shift));
+                // `-a*x == -b` moves the other way as `b` grows
+                if self.negated.get(i) == Some(&true) {
+                    let negate = |x: N| N::zero() - x;
+                    (i, upper.map(negate), lower.map(negate))
+                } else {
+                    (i, lower, upper)
+                }
+            })
+            .collect()
+    }
+
+    /// Ratio test of column `j`: the row that leaves the basis as the column enters it and the
+    /// value the column enters with, none if no row limits it
+    fn ratio_test(&self, j: usize) -> Option<(usize, N)> {
+        let (a, b) = (self.rows.slice(s![.., ..-1]), self.rows.slice(s![.., -1]));
+
+        a.column(j)
+            .iter()
+            .zip(b)
+            .enumerate()
+            .filter(|(_, (x, _))| **x > N::zero())
+            .map(|(i, (x, y))| (i, y.clone() / x.clone()))
+            .min_by(|x, y| x.1.cmp(&y.1))
+    }
+
+    /// Whether entering column `j` moves any decision variable rather than only slacks
+    fn moves_decisions(&self, j: usize) -> bool {
+        j < self.variables
+            || self
+                .basis_coeffs
+                .iter()
+                .enumerate()
+                .any(|(i, (column, _))| *column < self.variables && !self.rows[[i, j]].is_zero())
+    }
+
+    /// Whether the optimum is reached at more than one point, i.e. some non-basic column has a
+    /// zero reduced cost and moves the decision variables either by a positive step up to
+    /// another vertex or without limit along a ray
+    pub fn has_alternate_optima(&self) -> bool {
+        self.tied_columns()
+            .filter(|&j| self.moves_decisions(j))
+            .any(|j| match self.ratio_test(j) {
+                Some((_, step)) => step > N::zero(),
+                // no row limits the column, so the optimum stretches along a ray
+                None => true,
+            })
+    }
+
+    /// Values of the decision variables at another optimal vertex, found by one more pivot on
+    /// the first non-basic column with a zero reduced cost which some row limits to a positive
+    /// step. A zero step in a degenerate tableau would only give the same point under another
+    /// basis, and an unlimited column leads along a ray to no vertex, so neither counts.
+    pub fn alternate_vertex(&self) -> Option<Vec<N>> {
+        let a = self.rows.slice(s![.., ..-1]);
+
+        self.tied_columns()
+            .filter(|&j| self.moves_decisions(j))
+            .find_map(|j| {
+                let (row, step) = self.ratio_test(j).filter(|(_, x)| *x > N::zero())?;
+
+                let mut point = vec![N::zero(); self.variables];
+                for (i, (column, value)) in self.basis_coeffs.iter().enumerate() {
+                    if i != row && *column < self.variables {
+                        point[*column] = value.clone() - a[[i, j]].clone() * step.clone();
+                    }
+                }
+                if j < self.variables {
+                    point[j] = step;
+                }
+                Some(point)
+            })
+    }
+}
+
+impl<N: Num + NumAssign + Clone> Solution<N> {
+    /// The free term of the final objective row as it is stored in the tableau. The row holds
+    /// `z_j - c_j` with the constant of the objective negated before the row is inverted, so
+    /// the term already is the optimum with its sign for either goal.
+    pub fn raw_objective(&self) -> N {
+        self.coefficients[self.coefficients.len() - 1].clone()
+    }
+
+    /// The optimal value of the objective in the terms of the task, which is what [`Display`]
+    /// prints. Basic columns have zero reduced costs at the optimum, so it equals
+    /// [`Solution::raw_objective`] unless the objective row was left unreduced.
+    ///
+    /// It is the last value of [`Solution::objective_history`], which the solver sums up with
+    /// checked arithmetic, so an overflow is reported by [`SimplexSolver::solve`] instead.
+    pub fn objective_value(&self) -> N {
+        self.objectives
+            .last()
+            .cloned()
+            .unwrap_or_else(|| self.raw_objective())
+    }
+
+    /// The dual value of every restriction row: the multiplier the row was added to the
+    /// objective row with on the way to the final tableau, read off the columns of the starting
+    /// basis. It is how much the raw optimum grows per unit increase of the right-hand side, so
+    /// a `<=` restriction gets a non-negative value when maximizing and a non-positive one when
+    /// minimizing. With the Big-M method the values are relative to the taxed starting row, the
+    /// real parts being the dual values of the task.
+    ///
+    /// The right-hand sides are those the task gave, so the values of rows negated for the
+    /// standard form are negated back.
+    ///
+    /// # Panics
+    /// If the starting basis columns did not form an identity matrix
+    pub fn dual_values(&self) -> Array1<N> {
+        let basis = self
+            .initial_basis
+            .as_ref()
+            .expect("The starting basis is not an identity matrix");
+
+        basis
+            .iter()
+            .enumerate()
+            .map(|(i, &column)| {
+                let value = self.coefficients[column].clone() - self.initial_z[column].clone();
+                if self.negated.get(i) == Some(&true) {
+                    N::zero() - value
+                } else {
+                    value
+                }
+            })
+            .collect()
+    }
+
+    /// Values of the basic variables which are not zero, ordered by column
+    fn nonzero_values(&self) -> BTreeMap<usize, &N> {
+        self.basis_coeffs
+            .iter()
+            .chain(&self.fixed)
+            .filter(|(_, value)| !value.is_zero())
+            .map(|(i, value)| (*i, value))
+            .collect()
+    }
+}
+
+impl<N: Display + Num + NumAssign + Clone> Solution<N> {
+    /// The optimum followed by Markdown tables of the decision variables, of the slack left in
+    /// every restriction, equalities having none, and of the shadow price of every restriction
+    /// if the starting basis was an identity matrix
+    pub fn to_markdown(&self) -> String {
+        let values = self
+            .basis_coeffs
+            .iter()
+            .map(|(i, x)| (*i, x))
+            .collect::<BTreeMap<_, _>>();
+        let binding = self.binding_constraints();
+
+        let variables = self
+            .variable_values()
+            .into_iter()
+            .collect::<BTreeMap<_, _>>()
+            .into_iter()
+            .map(|(i, x)| vec![self.label(i - 1), x.to_string()])
+            .collect();
+        let restrictions = self
+            .slacks
+            .iter()
+            .enumerate()
+            .map(|(i, slack)| {
+                let slack = match slack {
+                    None => "-".to_owned(),
+                    Some(col) => values.get(col).map_or("0".to_owned(), |x| x.to_string()),
+                };
+                let binding = if binding.contains(&i) { "yes" } else { "no" };
+                vec![(i + 1).to_string(), slack, binding.to_owned()]
+            })
+            .collect();
+
+        let mut out = format!(
+            "Optimal z is: {}\n\n{}\n{}",
+            self.objective_value(),
+            markdown_table(&["Variable", "Value"], variables),
+            markdown_table(&["Restriction", "Slack", "Binding"], restrictions)
+        );
+        if let Some(basis) = &self.initial_basis {
+            let prices = self
+                .dual_values()
+                .into_iter()
+                .take(basis.len() - self.bound_rows)
+                .enumerate()
+                .map(|(i, x)| vec![(i + 1).to_string(), x.to_string()])
+                .collect();
+            out += &format!(
+                "\n{}",
+                markdown_table(&["Restriction", "Shadow price"], prices)
+            );
+        }
+        out
+    }
+
+    /// The optimum, the decision variables and the basis, see [`SolutionReport`]
+    pub fn report(&self) -> SolutionReport {
+        SolutionReport {
+            objective: self.objective_value().to_string(),
+            variables: self
+                .variable_values()
+                .into_iter()
+                .map(|(i, x)| (self.label(i - 1), x.to_string()))
+                .collect(),
+            basis: self
+                .basis_coeffs
+                .iter()
+                .map(|(i, _)| i + self.index_base)
+                .collect(),
+            iterations: self.iterations(),
+        }
+    }
+
+    /// [`Solution::report`] as a JSON object
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.report()).expect("A report is always serializable")
+    }
+}
+
+impl<N: Display> Solution<N> {
+    /// The pivots as CSV with a header row, iterations counting from 1 and columns and rows from
+    /// 0 as in the tableau
+    pub fn pivot_log(&self) -> String {
+        let mut csv = "iteration,entering_col,leaving_row,pivot_value,objective_after\n".to_owned();
+        for (i, pivot) in self.pivots.iter().enumerate() {
+            csv += &format!(
+                "{},{},{},{},{}\n",
+                i + 1,
+                pivot.column,
+                pivot.row,
+                pivot.value,
+                pivot.objective
+            );
+        }
+        csv
+    }
+}
+
+/// Pads every cell to the widest one in its column
+fn markdown_table(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let widths = header
+        .iter()
+        .enumerate()
+        .map(|(j, title)| {
+            rows.iter()
+                .map(|row| row[j].chars().count())
+                .chain([title.chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let line = |cells: Vec<String>| {
+        let cells = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>();
+        format!("| {} |\n", cells.join(" | "))
+    };
+
+    let mut table = line(header.iter().map(|x| x.to_string()).collect());
+    table += &line(widths.iter().map(|width| "-".repeat(*width)).collect());
+    for row in rows {
+        table += &line(row);
+    }
+    table
+}
+
+impl<T: Num + NumAssign + Clone> Solution<Tax<T>> {
+    /// The optimum as a plain number, or `None` if it has an `M` part. Solving a canonized task
+    /// never leaves one, as a nonzero artificial variable makes it infeasible instead, so only a
+    /// tableau built by hand, like by [`SimplexSolver::from_canonical_matrix`], may end up here
+    pub fn objective_rational(&self) -> Option<T> {
+        self.objective_value().real()
+    }
+}
+
+impl<N: Num + NumAssign + Clone + PartialOrd + Display> Solution<N> {
+    /// Checks the optimality conditions the final tableau must meet: basic variables are not
+    /// negative, no reduced cost improves the objective, basic columns have zero reduced costs,
+    /// and the objective equals the dual objective. The dual prices are the multipliers of the
+    /// starting rows in the final objective row, read under the starting basis, so the dual
+    /// objective is the starting free term plus the prices times the starting right-hand sides.
+    ///
+    /// A violation means a bug in the solver rather than a property of the task.
+    pub fn verify_optimality_conditions(&self) -> Result<(), OptimalityViolation> {
+        let basis = self
+            .initial_basis
+            .as_ref()
+            .ok_or(OptimalityViolation::NoDuals)?;
+        let reduced_costs = self.coefficients.slice(s![..-1]);
+
+        for (row, (column, value)) in self.basis_coeffs.iter().enumerate() {
+            if *value < N::zero() {
+                return Err(OptimalityViolation::PrimalInfeasible { row });
+            }
+            if !reduced_costs[*column].is_zero() {
+                return Err(OptimalityViolation::ComplementarySlackness { column: *column });
+            }
+        }
+        let improving = reduced_costs.iter().position(|x| match self.aim {
+            Goal::Minimize => *x > N::zero(),
+            Goal::Maximize => *x < N::zero(),
+        });
+        if let Some(column) = improving {
+            return Err(OptimalityViolation::DualInfeasible { column });
+        }
+
+        let mut dual = self.initial_z[self.initial_z.len() - 1].clone();
+        for (column, b) in basis.iter().zip(&self.initial_b) {
+            dual += reduced_costs[*column].clone() * b.clone();
+        }
+        match self.raw_objective() - dual {
+            gap if gap.is_zero() => Ok(()),
+            gap => Err(OptimalityViolation::DualityGap {
+                discrepancy: gap.to_string(),
+            }),
+        }
+    }
+}
+
+/// Every decision variable with its value, see [`Solution::iter`]
+impl<N: Zero + Clone + Sub<Output = N>> IntoIterator for Solution<N> {
+    type Item = (usize, N);
+    type IntoIter = btree_map::IntoIter<usize, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Solutions are equal when they reach the same objective at the same point, regardless of the
+/// order of their basis or degenerate basic variables
+impl<N: Num + NumAssign + Clone> PartialEq for Solution<N> {
+    fn eq(&self, other: &Self) -> bool {
+        self.objective_value() == other.objective_value()
+            && self.nonzero_values() == other.nonzero_values()
+    }
+}
+
+impl<F: Display + Num + NumAssign + Clone> Display for Solution<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Optimal z is: {}", self.objective_value())?;
         writeln!(f, "Base variables are equal to: ")?;
-        for &(i, item) in &self.basis_coeffs {
-            writeln!(f, "   x{} = {item}", i + 1)?;
+        for (i, item) in &self.basis_coeffs {
+            writeln!(f, "   {} = {item}", self.label(*i))?;
+        }
+        if !self.splits.is_empty() {
+            let point = self.point(self.variables);
+            writeln!(f, "Free variables are equal to: ")?;
+            for &(positive, negative) in &self.splits {
+                let value = point[positive].clone() - point[negative].clone();
+                writeln!(f, "   {} = {value}", self.label(positive))?;
+            }
+        }
+        if !self.fixed.is_empty() {
+            writeln!(f, "Substituted variables are equal to: ")?;
+            for (i, item) in &self.fixed {
+                writeln!(f, "   {} = {item}", self.label(*i))?;
+            }
+        }
+        writeln!(f, "Iterations: {}", self.iterations())?;
+        if let Some(method) = self.method {
+            let name = match method {
+                Method::Simple => "simplex method",
+                Method::Taxes => "Big-M",
+                Method::SecondPhase => "two-phase method",
+            };
+            writeln!(f, "Solved via: {name}")?;
         }
         writeln!(f,)?;
 
@@ -40,44 +846,56 @@ impl<F: Display + Num + NumAssign + Copy> Display for Solution<F> {
 
 impl<F> SimplexSolver<F> {
     #[inline]
-    fn z(&self) -> ArrayView1<F> {
+    fn z(&self) -> ArrayView1<'_, F> {
         self._contents.slice(s![-1, ..])
     }
 
     #[inline]
-    fn a(&self) -> ArrayView2<F> {
+    fn a(&self) -> ArrayView2<'_, F> {
         self._contents.slice(s![..-1, ..-1])
     }
 
     #[inline]
-    fn b(&self) -> ArrayView1<F> {
+    fn b(&self) -> ArrayView1<'_, F> {
         self._contents.slice(s![..-1, -1])
     }
 
-    /// # Panics
-    /// If either `N` or `M` is zero
-    #[allow(dead_code)]
+    /// The current tableau: the restriction rows followed by the objective row, the free term
+    /// being the last column
+    pub fn tableau(&self) -> ArrayView2<'_, F> {
+        self._contents.view()
+    }
+
+    /// Column of the basic variable of every restriction row
+    pub fn basis_indices(&self) -> ArrayView1<'_, usize> {
+        self.basis.view()
+    }
+
+    /// # Errors
+    /// [`SimplexBuildError::NoRestrictions`] or [`SimplexBuildError::NoVariables`] if either `M`
+    /// or `N` is zero
     pub fn from_canonical_matrix<const N: usize, const M: usize>(
         input: [[F; N]; M],
         z: [F; N],
         aim: Goal,
-    ) -> Self
+    ) -> Result<Self, SimplexBuildError>
     where
         F: Clone + Zero,
     {
         if input.is_empty() {
-            panic!("Given zero restrictions");
+            return Err(SimplexBuildError::NoRestrictions);
         }
         if z.is_empty() {
-            panic!("No variables to solve for");
+            return Err(SimplexBuildError::NoVariables);
         }
 
-        let mut matrix = Array2::from_shape_vec((M, N), input.flatten().to_vec()).unwrap();
+        let mut matrix =
+            Array2::from_shape_vec((M, N), input.into_iter().flatten().collect()).unwrap();
         let z = Array1::from_vec(z.to_vec());
 
         matrix.push_row(z.view()).unwrap();
 
-        Self {
+        Ok(Self {
             _contents: matrix,
             basis: z
                 .slice(s![..-1])
@@ -86,18 +904,50 @@ impl<F> SimplexSolver<F> {
                 .map(|x| x.0)
                 .collect(),
             aim,
-        }
+            slacks: vec![],
+            variables: N - 1,
+            rhs_weights: Array1::zeros(M),
+            style: OutputStyle::default(),
+            time_limit: None,
+            rule: AntiCycling::default(),
+            max_iterations: None,
+            method: None,
+            splits: vec![],
+            negated: vec![],
+            bound_rows: 0,
+            artificials: vec![],
+            observer: None,
+        })
     }
 
-    pub fn from_contents(contents: Array2<F>, aim: Goal) -> SimplexSolver<F>
+    /// Builds a solver from a combined tableau: restriction rows `A|b` followed by the
+    /// objective row
+    ///
+    /// # Errors
+    /// [`SimplexBuildError::NoRestrictions`] or [`SimplexBuildError::NoVariables`] if the
+    /// tableau has nothing but the objective row or the free term column
+    pub fn from_contents(contents: Array2<F>, aim: Goal) -> Result<Self, SimplexBuildError>
     where
         F: Zero + Clone,
     {
-        if contents.len_of(Axis(0)) == 0 {
-            panic!("Given zero restrictions")
+        if contents.nrows() < 2 {
+            return Err(SimplexBuildError::NoRestrictions);
+        }
+        if contents.ncols() < 2 {
+            return Err(SimplexBuildError::NoVariables);
         }
 
+        Ok(Self::from_tableau(contents, aim))
+    }
+
+    /// [`SimplexSolver::from_contents`] for a tableau which may have no restrictions, as an
+    /// empty task gives. Solving it reports [`SimplexMethodError::NoRestrictions`].
+    pub(crate) fn from_tableau(contents: Array2<F>, aim: Goal) -> Self
+    where
+        F: Zero + Clone,
+    {
         let z = contents.slice(s![-1, ..-1]).to_owned();
+        let rows = contents.len_of(Axis(0)) - 1;
 
         Self {
             _contents: contents,
@@ -107,36 +957,161 @@ impl<F> SimplexSolver<F> {
                 .map(|x| x.0)
                 .collect(),
             aim,
+            slacks: vec![],
+            variables: z.len(),
+            rhs_weights: Array1::zeros(rows),
+            style: OutputStyle::default(),
+            time_limit: None,
+            rule: AntiCycling::default(),
+            max_iterations: None,
+            method: None,
+            splits: vec![],
+            negated: vec![],
+            bound_rows: 0,
+            artificials: vec![],
+            observer: None,
         }
     }
 
+    /// Records how many of the first columns are decision variables, the rest being slack or
+    /// artificial ones
+    pub fn with_variables(mut self, variables: usize) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Records the slack or surplus column of each restriction, used for post-optimal reports
+    pub fn with_slacks(mut self, slacks: Vec<Option<usize>>) -> Self {
+        self.slacks = slacks;
+        self
+    }
+
+    /// Records how the objective row's free term depends on the right-hand sides, which is
+    /// needed to swap them in [`SimplexSolver::solve_scenarios`]
+    pub fn with_rhs_weights(mut self, rhs_weights: Array1<F>) -> Self {
+        self.rhs_weights = rhs_weights;
+        self
+    }
+
+    /// Prints every tableau while solving if the style asks for them
+    pub fn with_style(mut self, style: OutputStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Passes every tableau to `observer` instead of printing it as the style says. The caller
+    /// may keep a handle to the observer to read what it collected after solving.
+    pub fn with_observer(
+        mut self,
+        observer: Arc<Mutex<impl SimplexObserver<F> + Send + 'static>>,
+    ) -> Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    /// Chooses the pivots by `rule` instead of the default [`AntiCycling::Dantzig`]
+    pub fn with_rule(mut self, rule: AntiCycling) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    /// Gives up with [`SimplexMethodError::IterationLimitExceeded`] after `limit` pivots instead
+    /// of the default 100 per column
+    pub fn with_max_iterations(mut self, limit: Option<usize>) -> Self {
+        self.max_iterations = limit;
+        self
+    }
+
+    /// Records the canonicalization the tableau was built with
+    pub fn with_method(mut self, method: Method) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Columns of the two parts `x - x'` each free variable was split into, so that the solution
+    /// reports `x` itself
+    pub fn with_splits(mut self, splits: Vec<(usize, usize)>) -> Self {
+        self.splits = splits;
+        self
+    }
+
+    /// Restriction rows that were multiplied by -1 to get a nonnegative right-hand side, so that
+    /// post-optimal reports speak of the right-hand sides as they were given
+    pub fn with_negated(mut self, negated: Vec<bool>) -> Self {
+        self.negated = negated;
+        self
+    }
+
+    /// How many of the last restriction rows come from variable bounds rather than the
+    /// restrictions of the task, which post-optimal reports leave out
+    pub fn with_bound_rows(mut self, bound_rows: usize) -> Self {
+        self.bound_rows = bound_rows;
+        self
+    }
+
+    /// Columns of the artificial variables, any of them left positive at the optimum making
+    /// [`SimplexSolver::solve`] report the task infeasible
+    pub fn with_artificials(mut self, artificials: Vec<usize>) -> Self {
+        self.artificials = artificials;
+        self
+    }
+
+    /// Gives up with [`SimplexMethodError::TimeLimitExceeded`] once solving takes `limit`
+    pub fn with_time_limit(mut self, limit: Option<Duration>) -> Self {
+        self.time_limit = limit;
+        self
+    }
+
     fn is_optimal(&self) -> bool
     where
         F: Zero + PartialOrd,
     {
+        // the last column holds the objective value, not a reduced cost
+        let reduced_costs = self.z().slice_move(s![..-1]);
+
         match self.aim {
-            Goal::Minimize => self.z().iter().all(|x| *x <= F::zero()),
-            Goal::Maximize => self.z().iter().all(|x| *x >= F::zero()),
+            Goal::Minimize => reduced_costs.iter().all(|x| *x <= F::zero()),
+            Goal::Maximize => reduced_costs.iter().all(|x| *x >= F::zero()),
         }
     }
 
+    /// A cheap structural check: `false` if some column would improve the objective while no
+    /// restriction bounds it, i.e. it has no positive entry. Then the objective grows without
+    /// limit along that column, or the task has no feasible point if artificial variables are
+    /// left. `true` does not prove boundedness.
+    pub fn is_objective_bounded(&self) -> bool
+    where
+        F: Zero + PartialOrd,
+    {
+        self.z()
+            .iter()
+            .zip(self.a().columns())
+            .all(|(cost, column)| {
+                let improving = match self.aim {
+                    Goal::Minimize => *cost > F::zero(),
+                    Goal::Maximize => *cost < F::zero(),
+                };
+                !improving || column.iter().any(|x| *x > F::zero())
+            })
+    }
+
     fn pivot_column(&self) -> Result<usize, SimplexMethodError>
     where
-        F: Zero + Ord + Copy,
+        F: Zero + Ord,
     {
         let z = self.z();
+        let mut improving = z
+            .indexed_iter()
+            .take(z.len() - 1)
+            .filter(|(_, x)| match self.aim {
+                Goal::Minimize => **x > F::zero(),
+                Goal::Maximize => **x < F::zero(),
+            });
 
-        match self.aim {
-            Goal::Minimize => z
-                .indexed_iter()
-                .take(self.z().len() - 1)
-                .filter(|(_, x)| **x > F::zero())
-                .max_by_key(|x| x.1),
-            Goal::Maximize => z
-                .indexed_iter()
-                .take(self.z().len() - 1)
-                .filter(|(_, x)| **x < F::zero())
-                .min_by_key(|x| x.1),
+        match (self.rule, &self.aim) {
+            (AntiCycling::Bland, _) => improving.next(),
+            (AntiCycling::Dantzig, Goal::Minimize) => improving.max_by_key(|x| x.1),
+            (AntiCycling::Dantzig, Goal::Maximize) => improving.min_by_key(|x| x.1),
         }
         .map(|x| x.0)
         .ok_or(SimplexMethodError::NoSolutions)
@@ -144,40 +1119,100 @@ impl<F> SimplexSolver<F> {
 
     fn pivot_row(&self, pivot_col: usize) -> Result<usize, SimplexMethodError>
     where
-        F: Zero + Ord + Div<F, Output = F> + Copy,
+        F: Zero + Ord + CheckedDiv,
     {
         self.a()
             .column(pivot_col)
             .indexed_iter()
             .zip(self.b())
-            .filter(|((_, x), _)| !x.is_zero())
-            .map(|((i, x), y)| (i, *y / *x))
-            .filter(|(_, x)| !x.is_zero() && *x > F::zero())
-            .min_by_key(|x| x.1)
+            .filter(|((_, x), _)| **x > F::zero())
+            .map(|((i, x), y)| {
+                y.checked_div(x)
+                    .map(|ratio| (i, ratio))
+                    .ok_or(SimplexMethodError::Overflow)
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .min_by(|x, y| match self.rule {
+                AntiCycling::Dantzig => x.1.cmp(&y.1),
+                AntiCycling::Bland => x.1.cmp(&y.1).then(self.basis[x.0].cmp(&self.basis[y.0])),
+            })
             .map(|x| x.0)
-            .ok_or(SimplexMethodError::NoLimit)
+            .ok_or(SimplexMethodError::Unbounded)
     }
 
     fn pivot(&self) -> Result<(usize, usize, F), SimplexMethodError>
     where
-        F: Zero + Ord + Div<F, Output = F> + Copy,
+        F: Zero + Ord + CheckedDiv + Clone + Display,
     {
         let col = self.pivot_column()?;
-        let row = self.pivot_row(col)?;
+        let (row, col) = match self.pivot_row(col) {
+            // a ray never drives the artificial variables out, so a column which could is taken
+            // instead. Without one the `M` part is at its optimum and they can't be driven out.
+            Err(SimplexMethodError::Unbounded) if !self.penalty().is_zero() => {
+                let z = self.z();
+                (0..z.len() - 1)
+                    .filter(|j| match self.aim {
+                        Goal::Minimize => z[*j] > F::zero(),
+                        Goal::Maximize => z[*j] < F::zero(),
+                    })
+                    .find_map(|j| Some((self.pivot_row(j).ok()?, j)))
+                    .ok_or_else(|| SimplexMethodError::Infeasible {
+                        penalty: self.penalty().to_string(),
+                    })?
+            }
+            row => (row?, col),
+        };
 
-        Ok((row, col, self._contents[(row, col)]))
+        Ok((row, col, self._contents[(row, col)].clone()))
+    }
+
+    /// Sum of the artificial variables left in the basis, each costing `M` per unit
+    fn penalty(&self) -> F
+    where
+        F: Zero + Clone,
+    {
+        self.basis
+            .iter()
+            .zip(self.b())
+            .filter(|(i, _)| self.artificials.contains(i))
+            .fold(F::zero(), |sum, (_, x)| sum + x.clone())
+    }
+}
+
+/// Builds a solver from a combined tableau: restriction rows `A|b` followed by the objective row
+impl<F: Zero + Clone> TryFrom<(Array2<F>, Goal)> for SimplexSolver<F> {
+    type Error = SimplexBuildError;
+
+    fn try_from((contents, aim): (Array2<F>, Goal)) -> Result<Self, Self::Error> {
+        Self::from_contents(contents, aim)
     }
 }
 
 impl<T> SimplexSolver<T>
 where
-    T: Ord + Copy + LinalgScalar + Num + NumAssign + Display,
+    T: Ord + Clone + Num + NumAssign + Display + CheckedAdd + CheckedSub + CheckedMul + CheckedDiv,
 {
-    fn make_iteration(&mut self) -> Result<(), SimplexMethodError> {
-        let (p_row, p_col, pivot) = self.pivot()?;
+    fn make_iteration(&mut self) -> Result<Pivot<T>, SimplexMethodError> {
+        let (row, column, value) = self.pivot()?;
+        self.pivot_on(row, column)?;
 
-        let mut pivot_row = self._contents.row_mut(p_row);
-        pivot_row.map_inplace(|x| *x /= pivot);
+        Ok(Pivot {
+            column,
+            row,
+            value,
+            objective: self.objective()?,
+        })
+    }
+
+    /// Brings column `p_col` into the basis at row `p_row` using checked arithmetic, so that an
+    /// overflowing backend reports [`SimplexMethodError::Overflow`] instead of panicking
+    fn pivot_on(&mut self, p_row: usize, p_col: usize) -> Result<(), SimplexMethodError> {
+        let pivot = self._contents[(p_row, p_col)].clone();
+
+        for x in self._contents.row_mut(p_row) {
+            *x = x.checked_div(&pivot).ok_or(SimplexMethodError::Overflow)?;
+        }
         let pivot_row = self._contents.row(p_row).to_owned();
 
         for (i, mut row) in self._contents.rows_mut().into_iter().enumerate() {
@@ -185,9 +1220,14 @@ where
                 continue;
             }
 
-            let pivot_coeff = row[p_col];
+            let pivot_coeff = row[p_col].clone();
 
-            row.scaled_add(T::zero() - pivot_coeff, &pivot_row);
+            for (x, y) in row.iter_mut().zip(&pivot_row) {
+                *x = pivot_coeff
+                    .checked_mul(y)
+                    .and_then(|y| x.checked_sub(&y))
+                    .ok_or(SimplexMethodError::Overflow)?;
+            }
         }
 
         self.basis[p_row] = p_col;
@@ -195,34 +1235,1094 @@ where
         Ok(())
     }
 
+    /// Warm-starts from `basis`, the basic column of every restriction row, by pivoting each of
+    /// them in. The basis has to be non-singular and feasible for this tableau.
+    pub fn with_basis(mut self, basis: Vec<usize>) -> Result<Self, SimplexBuildError> {
+        let (rows, columns) = (self.basis.len(), self._contents.ncols() - 1);
+        if basis.len() != rows {
+            return Err(SimplexBuildError::BasisSize {
+                expected: rows,
+                found: basis.len(),
+            });
+        }
+
+        let mut pivoted = vec![false; rows];
+        for col in basis {
+            if col >= columns {
+                return Err(SimplexBuildError::InvalidBasis);
+            }
+            let row = (0..rows)
+                .find(|&i| !pivoted[i] && !self._contents[(i, col)].is_zero())
+                .ok_or(SimplexBuildError::InvalidBasis)?;
+            self.pivot_on(row, col)
+                .map_err(|_| SimplexBuildError::InvalidBasis)?;
+            pivoted[row] = true;
+        }
+
+        if self.b().iter().any(|x| *x < T::zero()) {
+            return Err(SimplexBuildError::InvalidBasis);
+        }
+        Ok(self)
+    }
+
+    /// [`SimplexSolver::with_basis`] reading the basis written by [`Solution::save_basis`]
+    pub fn load_basis(self, path: impl AsRef<Path>) -> Result<Self, SimplexBuildError> {
+        let file = read_to_string(path).map_err(|x| SimplexBuildError::BasisFile(x.to_string()))?;
+        let basis = file
+            .lines()
+            .map(|line| {
+                line.trim()
+                    .strip_prefix('x')
+                    .and_then(|x| x.parse::<usize>().ok())
+                    .filter(|x| *x > 0)
+                    .map(|x| x - 1)
+                    .ok_or_else(|| SimplexBuildError::BasisFile(format!("bad label `{line}`")))
+            })
+            .collect::<Result<_, _>>()?;
+
+        self.with_basis(basis)
+    }
+
     pub fn solve(mut self) -> Result<Solution<T>, SimplexMethodError> {
+        let start = Instant::now();
+        if self._contents.nrows() < 2 {
+            return Err(SimplexMethodError::NoRestrictions);
+        }
+        let initial = (self.style == OutputStyle::Pretty).then(|| self.render_tableau());
+        // with artificial variables left the check may as well fail for an infeasible task,
+        // which only solving tells apart
+        if self.artificials.is_empty() && !self.is_objective_bounded() {
+            return Err(SimplexMethodError::Unbounded);
+        }
+        let initial_basis = self.is_identity_basis().then(|| self.basis.to_vec());
+        let (initial_z, initial_b) = (self.z().to_owned(), self.b().to_owned());
+        let mut objectives = vec![self.objective()?];
+        let mut pivots = vec![];
+        let max_iterations = self.max_iterations.unwrap_or(100 * self._contents.ncols());
         while !self.is_optimal() {
+            if self
+                .time_limit
+                .is_some_and(|limit| start.elapsed() >= limit)
+            {
+                return Err(SimplexMethodError::TimeLimitExceeded);
+            }
+            if pivots.len() >= max_iterations {
+                return Err(SimplexMethodError::IterationLimitExceeded(pivots.len()));
+            }
             self.debug_state();
-            self.make_iteration()?;
+            let pivot = self.make_iteration()?;
+            objectives.push(pivot.objective.clone());
+            pivots.push(pivot);
         }
         self.debug_state();
+        if let Some(initial) = initial {
+            let last = self.render_tableau();
+            let width = initial.lines().chain(last.lines()).map(str::len).max();
+            println!("{initial}{}\n{last}", "-".repeat(width.unwrap_or_default()));
+        }
+
+        let penalty = self.penalty();
+        if !penalty.is_zero() {
+            return Err(SimplexMethodError::Infeasible {
+                penalty: penalty.to_string(),
+            });
+        }
 
         let basis_coeffs = self
             .basis
             .iter()
             .zip(self.b())
-            .map(|(i, x)| (*i, *x))
+            .map(|(i, x)| (*i, x.clone()))
             .collect();
+        let rows = self._contents.slice(s![..-1, ..]).to_owned();
         let solution = self._contents.slice_move(s![-1, ..]);
 
         Ok(Solution {
             basis_coeffs,
             coefficients: solution,
+            slacks: self.slacks,
+            variables: self.variables,
+            rows,
+            fixed: vec![],
+            objectives,
+            pivots,
+            initial_basis,
+            initial_z,
+            initial_b,
+            aim: self.aim,
+            index_base: 1,
+            method: self.method,
+            splits: self.splits,
+            negated: self.negated,
+            bound_rows: self.bound_rows,
         })
     }
 
+    /// Solves the built task once per right-hand side column, reusing the built tableau instead
+    /// of constructing the task again. Each column gives the right-hand sides of the canonical
+    /// restrictions, i.e. after negative ones were flipped.
+    ///
+    /// Every scenario starts from the optimal basis of the previous one, if it is still feasible
+    /// with the new right-hand sides, and from the built basis otherwise. The solutions report
+    /// their post-optimal data against the built tableau either way.
+    ///
+    /// # Panics
+    /// If a column's length differs from the number of restrictions
+    pub fn solve_scenarios(
+        &self,
+        rhs_columns: Vec<Array1<T>>,
+    ) -> Vec<Result<Solution<T>, SimplexMethodError>> {
+        let mut warm_basis: Option<Vec<usize>> = None;
+
+        rhs_columns
+            .into_iter()
+            .map(|rhs| {
+                assert_eq!(rhs.len(), self.rhs_weights.len(), "Wrong scenario length");
+
+                let mut scenario = self.clone();
+                let mut free = scenario.z()[scenario.z().len() - 1].clone();
+
+                for ((b, new), weight) in scenario
+                    ._contents
+                    .slice_mut(s![..-1, -1])
+                    .iter_mut()
+                    .zip(rhs)
+                    .zip(&self.rhs_weights)
+                {
+                    free += weight.clone() * (new.clone() - b.clone());
+                    *b = new;
+                }
+                *scenario._contents.last_mut().unwrap() = free;
+
+                let initial_basis = scenario
+                    .is_identity_basis()
+                    .then(|| scenario.basis.to_vec());
+                let (initial_z, initial_b) = (scenario.z().to_owned(), scenario.b().to_owned());
+                let warm = warm_basis
+                    .take()
+                    .and_then(|basis| scenario.clone().with_basis(basis).ok());
+
+                let mut solution = warm.unwrap_or(scenario).solve()?;
+                warm_basis = Some(solution.basis_coeffs.iter().map(|(i, _)| *i).collect());
+                solution.initial_basis = initial_basis;
+                solution.initial_z = initial_z;
+                solution.initial_b = initial_b;
+                Ok(solution)
+            })
+            .collect()
+    }
+
+    /// Column generation for tasks with too many columns to write down: solves the tableau
+    /// with the columns it has, then asks `pricing` for a column to add given the dual prices of
+    /// the restrictions, and solves again until `pricing` returns `None` or a column that would
+    /// not improve the objective.
+    ///
+    /// A column is the restriction coefficients followed by the objective coefficient of the
+    /// new variable. The starting basis must be slack columns of zero cost, so that the dual
+    /// prices are read from the objective row under them.
+    ///
+    /// # Panics
+    /// If the starting basis is not an identity matrix, or a column has the wrong length
+    pub fn solve_column_generation(
+        mut self,
+        mut pricing: impl FnMut(&Array1<T>) -> Option<Array1<T>>,
+    ) -> Result<Solution<T>, SimplexMethodError> {
+        assert!(
+            self.is_identity_basis(),
+            "The starting basis is not an identity matrix"
+        );
+        let starting = self.basis.clone();
+
+        loop {
+            let solution = self.clone().solve()?;
+            let prices = starting.mapv(|j| solution.coefficients[j].clone());
+            let Some(column) = pricing(&prices) else {
+                return Ok(solution);
+            };
+            assert_eq!(column.len(), prices.len() + 1, "Wrong column length");
+
+            // the starting basis costs nothing, so the new objective entry is `z_j - c_j = -c_j`
+            let (a, cost) = column.view().split_at(Axis(0), prices.len());
+            let entry = T::zero() - cost[0].clone();
+            let mut reduced_cost = entry.clone();
+            for (y, x) in prices.iter().zip(a) {
+                reduced_cost += y.clone() * x.clone();
+            }
+            let improving = match self.aim {
+                Goal::Minimize => reduced_cost > T::zero(),
+                Goal::Maximize => reduced_cost < T::zero(),
+            };
+            if !improving {
+                return Ok(solution);
+            }
+
+            let mut column = a.to_vec();
+            column.push(entry);
+            let column = Array2::from_shape_vec((column.len(), 1), column).unwrap();
+            let free = self._contents.ncols() - 1;
+            self._contents = concatenate(
+                Axis(1),
+                &[
+                    self._contents.slice(s![.., ..free]),
+                    column.view(),
+                    self._contents.slice(s![.., free..]),
+                ],
+            )
+            .unwrap();
+        }
+    }
+
+    /// The tableau with a header of column labels and the basic variable of every row, each
+    /// column as wide as its widest cell
+    fn render_tableau(&self) -> String {
+        let columns = self._contents.ncols();
+        let header = ["basis".to_owned()]
+            .into_iter()
+            .chain((1..columns).map(|j| format!("x{j}")))
+            .chain(["b".to_owned()])
+            .collect::<Vec<_>>();
+        let rows = self
+            ._contents
+            .outer_iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let label = match self.basis.get(i) {
+                    Some(j) => format!("x{}", j + 1),
+                    None => "z".to_owned(),
+                };
+                [label]
+                    .into_iter()
+                    .chain(row.iter().map(|x| x.to_string()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let widths = (0..=columns)
+            .map(|j| {
+                rows.iter()
+                    .chain([&header])
+                    .map(|row| row[j].chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let mut table = String::new();
+        for row in [&header].into_iter().chain(&rows) {
+            let cells = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:>width$}"))
+                .collect::<Vec<_>>();
+            table += &cells.join(" | ");
+            table += "\n";
+        }
+        table
+    }
+
+    /// Whether the basis columns are the columns of the identity matrix in row order
+    fn is_identity_basis(&self) -> bool {
+        let a = self.a();
+        self.basis.len() == a.nrows()
+            && self.basis.iter().enumerate().all(|(row, col)| {
+                a.column(*col)
+                    .indexed_iter()
+                    .all(|(i, x)| if i == row { x.is_one() } else { x.is_zero() })
+            })
+    }
+
+    /// Objective value of the current basis, summed up with checked arithmetic like the pivots
+    fn objective(&self) -> Result<T, SimplexMethodError> {
+        let z = self.z();
+        self.basis.iter().zip(self.b()).try_fold(
+            z[z.len() - 1].clone(),
+            |objective, (col, value)| {
+                z[*col]
+                    .checked_mul(value)
+                    .and_then(|x| objective.checked_add(&x))
+                    .ok_or(SimplexMethodError::Overflow)
+            },
+        )
+    }
+
     fn debug_state(&self) {
-        for row in self._contents.outer_iter() {
-            for item in &row {
-                print!("{:<14} ", item.to_string());
+        match &self.observer {
+            Some(observer) => observer
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .on_iteration(self.tableau(), self.basis_indices()),
+            None if self.style.tableaux() => {
+                StdoutObserver.on_iteration(self.tableau(), self.basis_indices())
             }
-            println!();
+            None => (),
+        }
+    }
+}
+
+impl<T> SimplexSolver<Tax<T>>
+where
+    T: Clone + Num,
+    Tax<T>: Ord + Clone + Num + NumAssign + Display,
+    Tax<T>: CheckedAdd + CheckedSub + CheckedMul + CheckedDiv,
+{
+    /// The smallest and the largest value of `x1` and `x2` over the feasible region, for plotting
+    /// a task of two variables. Each is found by solving the task again with `x1` or `x2` as the
+    /// objective. `None` for a task of any other size, or an empty or unbounded region.
+    pub fn bounding_box(&self) -> Option<[(T, T); 2]> {
+        if self.variables != 2 {
+            return None;
+        }
+
+        // optimizes `sign * x{column + 1}` towards the task's goal, so that the `M` part of the
+        // objective row, which drives the artificial variables out, stays valid
+        let optimum = |column: usize, sign: T| {
+            let mut solver = self.clone();
+            solver.style = OutputStyle::default();
+            for (j, x) in solver._contents.slice_mut(s![-1, ..]).indexed_iter_mut() {
+                let cost = if j == column { sign.clone() } else { T::zero() };
+                *x = x.clone().with_real(T::zero() - cost);
+            }
+            let value = solver.solve().ok()?.objective_value().real()?;
+            Some(value * sign)
+        };
+        let range = |column| {
+            let along = optimum(column, T::one())?;
+            let against = optimum(column, T::zero() - T::one())?;
+            Some(match self.aim {
+                Goal::Maximize => (against, along),
+                Goal::Minimize => (along, against),
+            })
+        };
+
+        Some([range(0)?, range(1)?])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array1, Array2, ArrayView1, ArrayView2};
+    use num::Rational64;
+
+    use rstest::rstest;
+
+    use std::{
+        collections::{BTreeMap, HashMap},
+        env, fs, process,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use crate::{
+        errors::{OptimalityViolation, SimplexBuildError, SimplexMethodError},
+        parser::{Goal, Task},
+        simplex::{
+            AntiCycling, OutputFormat, SimplexObserver, SimplexSolver, Solution, SolutionReport,
+        },
+        solve_task,
+        task::{Simple, SimplexTask, SimplexTaskBuilder, Taxes},
+        tax_numbers::Tax,
+    };
+
+    /// Solves `src` by the simple method
+    fn solved(src: &str) -> Solution<Rational64> {
+        let task: Task = src.parse().unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        solver.solve().unwrap()
+    }
+
+    fn solution(basis_coeffs: Vec<(usize, i64)>) -> Solution<Rational64> {
+        Solution {
+            basis_coeffs: basis_coeffs
+                .into_iter()
+                .map(|(i, x)| (i, x.into()))
+                .collect(),
+            coefficients: array![0, 0, 1, 0, 7].mapv(Rational64::from),
+            slacks: vec![],
+            variables: 4,
+            rows: Array2::zeros((0, 5)),
+            fixed: vec![],
+            objectives: vec![],
+            pivots: vec![],
+            initial_basis: None,
+            initial_z: Array1::zeros(5),
+            initial_b: Array1::zeros(0),
+            aim: Goal::Maximize,
+            index_base: 1,
+            method: None,
+            splits: vec![],
+            negated: vec![],
+            bound_rows: 0,
+        }
+    }
+
+    /// Beale's task, on which the largest coefficient rule cycles through six degenerate bases
+    fn beale() -> SimplexSolver<Rational64> {
+        let r = |n, d| Rational64::new(n, d);
+        let [zero, one] = [r(0, 1), r(1, 1)];
+        let [a, b, c, d] = [r(3, 4), r(-20, 1), r(1, 2), r(-6, 1)];
+        SimplexSolver::from_canonical_matrix(
+            [
+                [one, zero, zero, r(1, 4), r(-8, 1), r(-1, 1), r(9, 1), zero],
+                [zero, one, zero, r(1, 2), r(-12, 1), r(-1, 2), r(3, 1), zero],
+                [zero, zero, one, zero, zero, one, zero, one],
+            ],
+            // minimizing -3/4 x4 + 20 x5 - 1/2 x6 + 6 x7, the row holds z_j - c_j
+            [zero, zero, zero, a, b, c, d, zero],
+            Goal::Minimize,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_iteration_limit() {
+        assert!(matches!(
+            beale().with_max_iterations(Some(20)).solve(),
+            Err(SimplexMethodError::IterationLimitExceeded(20))
+        ));
+        // the default limit stops cycling too
+        assert!(matches!(
+            beale().solve(),
+            Err(SimplexMethodError::IterationLimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn test_column_generation() {
+        // rolls of width 5 are cut into at most 4 pieces of width 2 and 2 of width 3, using at
+        // most 4 rolls and as much of their width as possible. The master starts with the
+        // pattern of two 2s, the pricing oracle offers one 3 and then a 2 with a 3.
+        let one = Rational64::from;
+        let solver = SimplexSolver::from_canonical_matrix(
+            [
+                [one(1), one(1), one(0), one(0), one(4)],
+                [one(2), one(0), one(1), one(0), one(4)],
+                [one(0), one(0), one(0), one(1), one(2)],
+            ],
+            [one(-4), one(0), one(0), one(0), one(0)],
+            Goal::Maximize,
+        )
+        .unwrap();
+        let mut patterns = vec![array![1, 1, 1, 5], array![1, 0, 1, 3]];
+        let mut rounds = 0;
+
+        let solution = solver
+            .solve_column_generation(|prices| {
+                assert_eq!(prices.len(), 3);
+                rounds += 1;
+                patterns.pop().map(|x| x.mapv(Rational64::from))
+            })
+            .unwrap();
+
+        // the width of all the pieces that may be cut is 2 * 4 + 3 * 2
+        assert_eq!(solution.objective_value(), one(14));
+        assert_eq!(rounds, 3);
+    }
+
+    #[test]
+    fn test_bland_rule() {
+        let solution = beale().with_rule(AntiCycling::Bland).solve().unwrap();
+        assert!(solution.to_string().contains("Optimal z is: -5/4\n"));
+    }
+
+    #[test]
+    fn test_from_canonical_matrix() {
+        let one = Rational64::from;
+        let solver = SimplexSolver::from_canonical_matrix(
+            [
+                [one(1), one(1), one(0), one(4)],
+                [one(2), one(0), one(1), one(6)],
+            ],
+            [one(-3), one(-2), one(0), one(0)],
+            Goal::Maximize,
+        )
+        .unwrap();
+
+        assert_eq!(
+            solver._contents,
+            array![[1, 1, 0, 4], [2, 0, 1, 6], [-3, -2, 0, 0]].mapv(Rational64::from)
+        );
+        assert_eq!(solver.basis, array![2]);
+
+        let empty: [[Rational64; 3]; 0] = [];
+        let solver = SimplexSolver::from_canonical_matrix(empty, [one(0); 3], Goal::Maximize);
+        assert_eq!(solver.err(), Some(SimplexBuildError::NoRestrictions));
+    }
+
+    #[test]
+    fn test_no_restrictions() {
+        let task = SimplexTaskBuilder::new()
+            .objective(&[(1, Rational64::from(1))])
+            .build::<Rational64>();
+        let solver = SimplexSolver::from(task.canonize::<Simple>());
+
+        assert!(matches!(
+            solver.solve(),
+            Err(SimplexMethodError::NoRestrictions)
+        ));
+    }
+
+    #[rstest]
+    #[case(Array2::zeros((3, 3)), Ok(()))]
+    #[case(Array2::zeros((1, 3)), Err(SimplexBuildError::NoRestrictions))]
+    #[case(Array2::zeros((3, 1)), Err(SimplexBuildError::NoVariables))]
+    fn test_try_from_contents(
+        #[case] contents: Array2<Rational64>,
+        #[case] expected: Result<(), SimplexBuildError>,
+    ) {
+        let solver = SimplexSolver::try_from((contents, Goal::Maximize));
+
+        assert_eq!(solver.map(|_| ()), expected);
+    }
+
+    #[test]
+    fn test_optimality_conditions() {
+        let solve = |input: &str| {
+            let task: Task = input.parse().unwrap();
+            let task: SimplexTask<Tax<Rational64>> = task.into();
+            SimplexSolver::from(task.canonize::<Taxes>())
+                .solve()
+                .unwrap()
+        };
+        let max = solve("x1 + x2 <= 4\nx1 + 3x2 <= 6\nz = 3x1 + 2x2 -> max");
+        let min = solve("x1 + x2 >= 2\nx1 - x2 <= 1\nz = x1 + 3x2 -> min");
+        assert_eq!(max.verify_optimality_conditions(), Ok(()));
+        assert_eq!(min.verify_optimality_conditions(), Ok(()));
+
+        let mut corrupted = max;
+        let free = corrupted.coefficients.len() - 1;
+        corrupted.coefficients[free] += Tax::from(Rational64::from(1));
+        let violation = corrupted.verify_optimality_conditions().unwrap_err();
+        assert_eq!(
+            violation,
+            OptimalityViolation::DualityGap {
+                discrepancy: "1".to_owned()
+            }
+        );
+        assert_eq!(
+            violation.to_string(),
+            "the objective differs from the dual objective by 1"
+        );
+    }
+
+    #[test]
+    fn test_basis_round_trip() {
+        let solver = || {
+            let task: Task = "x1 + x2 <= 4\nx1 + 3x2 <= 6\nz = 3x1 + 2x2 -> max"
+                .parse()
+                .unwrap();
+            let task: SimplexTask<Tax<Rational64>> = task.into();
+            SimplexSolver::from(task.canonize::<Simple>())
+        };
+        let path = env::temp_dir().join(format!("simplex-basis-{}.txt", process::id()));
+
+        let solution = solver().solve().unwrap();
+        solution.save_basis(&path).unwrap();
+        let warm = solver().load_basis(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(warm.is_optimal());
+        assert!(warm.solve().unwrap() == solution);
+        assert_eq!(
+            solver().with_basis(vec![0]).err(),
+            Some(SimplexBuildError::BasisSize {
+                expected: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_solution_eq() {
+        assert!(solution(vec![(0, 4), (1, 3), (3, 0)]) == solution(vec![(1, 3), (0, 4), (2, 0)]));
+        assert!(solution(vec![(0, 4), (1, 3)]) != solution(vec![(0, 3), (1, 4)]));
+    }
+
+    #[test]
+    fn test_output_format() {
+        assert_eq!("md".parse(), Ok(OutputFormat::Markdown));
+        #[cfg(feature = "serde")]
+        assert_eq!("json".parse(), Ok(OutputFormat::Json));
+
+        let err = "csv".parse::<OutputFormat>().unwrap_err();
+        assert!(err.ends_with(if cfg!(feature = "serde") {
+            "expected text, md or json"
+        } else {
+            "expected text or md"
+        }));
+    }
+
+    /// Keeps the basis of every tableau it sees
+    #[derive(Default)]
+    struct Recorder(Vec<Vec<usize>>);
+
+    impl SimplexObserver<Rational64> for Recorder {
+        fn on_iteration(&mut self, _: ArrayView2<'_, Rational64>, basis: ArrayView1<'_, usize>) {
+            self.0.push(basis.to_vec());
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        let task: Task = "x1 + x2 <= 4\nx1 <= 3\nz = 2x1 + x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        let recorder = Arc::new(Mutex::new(Recorder::default()));
+        let solver = SimplexSolver::from(task.canonize::<Simple>()).with_observer(recorder.clone());
+        // a solver with an observer can still be moved to another thread
+        fn shared<T: Send + Sync>(_: &T) {}
+        shared(&solver);
+
+        let solution = solver.solve().unwrap();
+
+        // the starting basis, then one after every pivot
+        let bases = &recorder.lock().unwrap().0;
+        assert_eq!(bases.len(), solution.iterations() + 1);
+        assert_eq!(bases[0], [2, 3]);
+        assert_eq!(bases.last().unwrap(), &[1, 0]);
+    }
+
+    #[test]
+    fn test_variable_ranges() {
+        // x1 = 4 is optimal: x2 may grow until 2x2 eats the second slack, s1 until x1 is zero
+        let solution = solved("x1 + x2 <= 4\nx1 + 3x2 <= 6\nz = 3x1 + 2x2 -> max");
+
+        assert_eq!(
+            solution.variable_ranges(),
+            vec![
+                (0, None),
+                (1, Some(1.into())),
+                (2, Some(4.into())),
+                (3, None)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_time_limit() {
+        let task: Task = "x1 <= 2\nx2 <= 3\nx1 + x2 <= 10\nz = x1 + x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+
+        assert!(matches!(
+            solver.with_time_limit(Some(Duration::ZERO)).solve(),
+            Err(SimplexMethodError::TimeLimitExceeded)
+        ));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let markdown = solved("x1 <= 2\nx2 <= 3\nx1 + x2 <= 10\nz = x1 + x2 -> max").to_markdown();
+        let rows = markdown
+            .lines()
+            .filter(|x| x.starts_with('|'))
+            .collect::<Vec<_>>();
+
+        assert!(rows.contains(&"| Variable | Value |"));
+        assert!(rows.contains(&"| Restriction | Slack | Binding |"));
+        assert!(rows.contains(&"| Restriction | Shadow price |"));
+        // three headers with their separators, the two decision variables, and three
+        // restrictions in each of the other tables
+        assert_eq!(rows.len(), 3 * 2 + 2 + 3 + 3);
+        assert!(rows.contains(&"| 3           | 5     | no      |"));
+        assert!(rows.contains(&"| 3           | 0            |"));
+        // the basic slack of the third restriction is not listed as a variable
+        assert!(!rows.iter().any(|x| x.starts_with("| x5")));
+    }
+
+    #[test]
+    fn test_to_vector() {
+        let x = solved("6x1 + 4x2 <= 24\nx1 + 2x2 <= 6\nz = 5x1 + 4x2 -> max").to_vector(2);
+
+        let a = array![[6, 4], [1, 2]].mapv(Rational64::from);
+        let b = array![24, 6].mapv(Rational64::from);
+        assert_eq!(x.len(), 2);
+        assert!(a.dot(&x).iter().zip(&b).all(|(ax, b)| ax <= b));
+    }
+
+    #[test]
+    fn test_objective_bounded() {
+        let solver = |input: &str| {
+            let task: Task = input.parse().unwrap();
+            let task: SimplexTask<Rational64> = task.into();
+            SimplexSolver::from(task.canonize::<Simple>())
+        };
+
+        assert!(solver("x1 + x2 <= 3\nz = x2 -> max").is_objective_bounded());
+        let unbounded = solver("x1 - x2 <= 3\nz = x2 -> max");
+        assert!(!unbounded.is_objective_bounded());
+        assert!(matches!(
+            unbounded.solve(),
+            Err(SimplexMethodError::Unbounded)
+        ));
+
+        // x2 is free to grow, but no x1 satisfies both restrictions
+        let task: Task = "x1 >= 2\nx1 <= 1\nz = x1 + x2 -> max".parse().unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let infeasible = SimplexSolver::from(task.canonize::<Taxes>());
+        assert!(!infeasible.is_objective_bounded());
+        assert!(matches!(
+            infeasible.solve(),
+            Err(SimplexMethodError::Infeasible { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pivot_log() {
+        assert_eq!(
+            solved("x1 <= 2\nx2 <= 3\nx1 + x2 <= 4\nz = 2x1 + x2 -> max").pivot_log(),
+            "iteration,entering_col,leaving_row,pivot_value,objective_after\n\
+             1,0,0,1,4\n\
+             2,1,2,1,6\n"
+        );
+    }
+
+    #[test]
+    fn test_objective_value() {
+        let solution = solved("x1 + x2 <= 2\nx1 <= 1\nz = x1 - 3x2 -> min");
+
+        // the minimum is at x2 = 2, and the stored free term already has its sign
+        assert_eq!(solution.objective_value(), Rational64::from(-6));
+        assert_eq!(solution.raw_objective(), solution.objective_value());
+
+        assert_eq!(
+            solved("x1 + x2 <= 2\nx1 <= 1\nz = x1 - 3x2 -> max").objective_value(),
+            Rational64::from(1)
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let solution = solved("x1 + x2 <= 4\nx1 <= 3\nz = 2x1 + x2 -> max");
+
+        assert_eq!(
+            solution.report(),
+            SolutionReport {
+                objective: "7".to_owned(),
+                variables: BTreeMap::from([
+                    ("x1".to_owned(), "3".to_owned()),
+                    ("x2".to_owned(), "1".to_owned())
+                ]),
+                basis: vec![2, 1],
+                iterations: 2,
+            }
+        );
+        #[cfg(feature = "serde")]
+        assert!(solution.to_json().contains("\"objective\": \"7\""));
+    }
+
+    #[test]
+    fn test_dual_values() {
+        let r = |n| Rational64::new(n, 2);
+        let cases = [
+            ("z = 3x1 + 5x2 -> max", array![r(0), r(3), r(2)]),
+            ("z = -3x1 + -5x2 -> min", array![r(0), r(-3), r(-2)]),
+        ];
+
+        for (target, duals) in cases {
+            let solution = solved(&format!("x1 <= 4\n2x2 <= 12\n3x1 + 2x2 <= 18\n{target}"));
+
+            assert_eq!(solution.dual_values(), duals);
+            // strong duality: the right-hand sides weighted by the dual values give the optimum
+            let b = array![4, 12, 18].mapv(Rational64::from);
+            assert_eq!(b.dot(&duals), solution.objective_value());
+        }
+
+        // raising the right-hand side of `-x1 >= -4` lowers the bound on x1 and so the optimum
+        let r = Rational64::from;
+        assert_eq!(solved("x1 <= 4\nz = x1 -> max").dual_values(), array![r(1)]);
+        assert_eq!(
+            solved("-x1 >= -4\nz = x1 -> max").dual_values(),
+            array![r(-1)]
+        );
+        assert_eq!(
+            solved("-x1 - x2 >= -6\nz = x1 + 2x2 -> max").dual_values(),
+            array![r(-2)]
+        );
+    }
+
+    #[test]
+    fn test_goal_symmetry() {
+        let optimum = |target: &str| {
+            solved(&format!("x1 <= 4\n2x2 <= 12\n3x1 + 2x2 <= 18\n{target}")).objective_value()
+        };
+
+        // minimizing an objective is maximizing its negation
+        for (objective, negated) in [("3x1 + 5x2", "-3x1 - 5x2"), ("x1 - 2x2", "-x1 + 2x2")] {
+            let min = optimum(&format!("z = {objective} -> min"));
+            let max = optimum(&format!("z = {negated} -> max"));
+
+            assert_eq!(min, -max);
+        }
+        assert_eq!(optimum("z = -3x1 - 5x2 -> min"), Rational64::from(-36));
+        assert_eq!(optimum("z = x1 - 2x2 -> min"), Rational64::from(-12));
+    }
+
+    #[test]
+    fn test_alternate_optima() {
+        let solve =
+            |objective: &str| solved(&format!("x1 + x2 <= 4\nx1 <= 3\nz = {objective} -> max"));
+
+        // the objective is parallel to the edge of the first restriction
+        let solution = solve("x1 + x2");
+        let first = solution.point(2);
+        let second = solution.alternate_vertex().unwrap();
+        assert!(solution.has_alternate_optima());
+        assert_ne!(first, second);
+        for point in [first, second] {
+            assert_eq!(point[0] + point[1], Rational64::from(4));
         }
-        println!("Basic: {}", self.basis);
+
+        assert!(!solve("2x1 + x2").has_alternate_optima());
+
+        // degenerate, x2 ties with x1 but can't move away from (4, 0)
+        let degenerate = solved("x1 <= 4\nx1 + x2 <= 4\nz = x1 -> max");
+        assert!(!degenerate.has_alternate_optima());
+        assert_eq!(degenerate.alternate_vertex(), None);
+        // x1 = 3 with any x2 >= 2, a ray with no second vertex
+        let ray = solved("x1 - x2 <= 1\nx1 <= 3\nz = x1 -> max");
+        assert!(ray.has_alternate_optima());
+        assert_eq!(ray.alternate_vertex(), None);
+    }
+
+    #[test]
+    fn test_objective_ranging() {
+        let ranges = |target: &str| {
+            let solution = solved(&format!("x1 <= 4\n2x2 <= 12\n3x1 + 2x2 <= 18\n{target}"));
+            let mut ranges = solution.objective_ranging();
+            ranges.sort_by_key(|x| x.0);
+            ranges
+        };
+        let r = |n, d| Some(Rational64::new(n, d));
+
+        // x1 = 2 and x2 = 6 stay optimal while c1 is within [0, 15/2] and c2 is at least 2
+        assert_eq!(
+            ranges("z = 3x1 + 5x2 -> max"),
+            [(0, r(0, 1), r(15, 2)), (1, r(2, 1), None)]
+        );
+        assert_eq!(
+            ranges("z = -3x1 - 5x2 -> min"),
+            [(0, r(-15, 2), r(0, 1)), (1, None, r(-2, 1))]
+        );
+    }
+
+    #[test]
+    fn test_rhs_ranging() {
+        let r = |x| Some(Rational64::from(x));
+
+        // the first restriction has slack 2 left, the other two are binding
+        assert_eq!(
+            solved("x1 <= 4\n2x2 <= 12\n3x1 + 2x2 <= 18\nz = 3x1 + 5x2 -> max").rhs_ranging(),
+            [(0, r(2), None), (1, r(6), r(18)), (2, r(12), r(24))]
+        );
+
+        // the same task with the second restriction negated and a bound which isn't reported
+        assert_eq!(
+            solved("x1 <= 4\n-2x2 >= -12\n3x1 + 2x2 <= 18\nz = 3x1 + 5x2 -> max\nx1 <= 10")
+                .rhs_ranging(),
+            [(0, r(2), None), (1, r(-18), r(-6)), (2, r(12), r(24))]
+        );
+    }
+
+    #[test]
+    fn test_tableau() {
+        let task: Task = "x1 + x2 <= 4\nx1 <= 3\nz = 2x1 + x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        let r = Rational64::from;
+
+        assert_eq!(
+            solver.tableau(),
+            array![
+                [r(1), r(1), r(1), r(0), r(4)],
+                [r(1), r(0), r(0), r(1), r(3)],
+                [r(-2), r(-1), r(0), r(0), r(0)]
+            ]
+        );
+        assert_eq!(solver.basis_indices(), array![2, 3]);
+
+        // x1 enters for the second slack, then x2 for the first one
+        let solution = solver.solve().unwrap();
+        assert_eq!(
+            solution.tableau(),
+            array![
+                [r(0), r(1), r(1), r(-1), r(1)],
+                [r(1), r(0), r(0), r(1), r(3)],
+                [r(0), r(0), r(1), r(1), r(7)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_solution_iter() {
+        let solution = solved("x1 + x2 <= 4\nx1 <= 5\nz = 3x1 + x2 -> max");
+
+        let values = solution.variable_values();
+        let mut indices = vec![];
+        for (i, value) in solution {
+            assert_eq!(values[&i], value);
+            indices.push(i);
+        }
+        // the non-basic x2 is listed as well, in order
+        assert_eq!(indices, [1, 2]);
+    }
+
+    #[test]
+    fn test_unbounded() {
+        // x1 enters at once, then x2 may grow x1 without bound
+        let task: Task = "x1 - x2 <= 1\nz = x1 -> max".parse().unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        let solver = SimplexSolver::from(task.canonize::<Simple>());
+
+        assert!(solver.is_objective_bounded());
+        assert!(matches!(solver.solve(), Err(SimplexMethodError::Unbounded)));
+    }
+
+    #[test]
+    fn test_binding_constraints() {
+        let solution = solved("x1 <= 2\nx2 <= 3\nx1 + x2 <= 10\nz = x1 + x2 -> max");
+
+        assert_eq!(solution.binding_constraints(), vec![0, 1]);
+    }
+
+    #[test]
+    fn test_basis_inverse() {
+        let solution = solved("x1 <= 4\n2x2 <= 12\n3x1 + 2x2 <= 18\nz = 3x1 + 5x2 -> max");
+        let r = |n, d| Rational64::new(n, d);
+
+        // x2 enters first, then x1, leaving x3, x2 and x1 basic in the first, second and third row
+        assert_eq!(solution.iterations(), 2);
+        assert_eq!(
+            solution.basis_inverse(),
+            array![
+                [r(1, 1), r(1, 3), r(-1, 3)],
+                [r(0, 1), r(1, 2), r(0, 1)],
+                [r(0, 1), r(-1, 3), r(1, 3)]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_variable_values() {
+        let values = solved("x1 + x2 <= 4\nx1 <= 5\nz = 3x1 + x2 -> max").variable_values();
+
+        // the slacks of both restrictions are left out, x2 is non-basic
+        assert_eq!(values, HashMap::from([(1, 4.into()), (2, 0.into())]));
+    }
+
+    #[test]
+    fn test_objective_history() {
+        let history = solved("x1 <= 2\nx2 <= 3\nx1 + x2 <= 4\nz = 2x1 + x2 -> max")
+            .objective_history()
+            .to_vec();
+
+        assert!(history.len() > 2);
+        assert!(history.windows(2).all(|x| x[0] <= x[1]));
+        assert_eq!(history.last(), Some(&6.into()));
+
+        let min: Task = "x1 + x2 >= 2\nx1 - x2 <= 1\nz = x1 + 3x2 -> min\nsolve using taxes"
+            .parse()
+            .unwrap();
+        let history = solve_task(min).unwrap().objective_history().to_vec();
+
+        assert!(history.len() > 1);
+        assert!(history.windows(2).all(|x| x[0] >= x[1]));
+    }
+
+    #[test]
+    fn test_solve_scenarios() {
+        let task: Task = "x1 <= 4\nx1 + x2 == 5\nz = x1 + 2x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let scenarios = [array![4, 5], array![4, 2]]
+            .into_iter()
+            .map(|x| x.mapv(|y| Tax::from(Rational64::from(y))))
+            .collect();
+
+        let solutions = solver
+            .solve_scenarios(scenarios)
+            .into_iter()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        let optima = solutions
+            .iter()
+            .map(|x| x.to_string().lines().next().unwrap().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(optima, vec!["Optimal z is: 10", "Optimal z is: 4"]);
+        // the second scenario starts from the first one's optimal basis, which is still optimal
+        assert!(solutions[0].iterations() > 0);
+        assert_eq!(solutions[1].iterations(), 0);
+
+        // the basis of x1 = 3, x2 = 1 turns infeasible at the second scenario's x2 = -1
+        let task: Task = "x1 + x2 <= 4\nx1 - x2 <= 2\nz = 2x1 + x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        let optima = solver
+            .solve_scenarios(vec![
+                array![4, 2].mapv(Rational64::from),
+                array![4, 6].mapv(Rational64::from),
+            ])
+            .into_iter()
+            .map(|x| x.unwrap().objective_value())
+            .collect::<Vec<_>>();
+        assert_eq!(optima, vec![7.into(), 8.into()]);
+    }
+
+    #[test]
+    fn test_objective_rational() {
+        let task: Task = "x1 + x2 == 4\nx1 <= 3\nz = x1 + 2x2 -> max\nsolve using taxes"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            solve_task(task).unwrap().objective_rational(),
+            Some(8.into())
+        );
+
+        // a tableau built by hand hides its artificial variables from the solver, so an `M` part
+        // of the objective outlives solving
+        let tax = |re: i64, m: i64| Tax::from((Rational64::from(re), Rational64::from(m)));
+        let solver = SimplexSolver::from_canonical_matrix(
+            [[tax(1, 0), tax(1, 0), tax(2, 0)]],
+            [tax(0, 0), tax(1, 0), tax(2, 1)],
+            Goal::Maximize,
+        )
+        .unwrap();
+        assert_eq!(solver.solve().unwrap().objective_rational(), None);
+    }
+
+    #[test]
+    fn test_infeasible() {
+        let task: Task = "x1 + x2 >= 5\nx1 <= 1\nx2 <= 1\nz = x1 -> max\nsolve using taxes"
+            .parse()
+            .unwrap();
+
+        // x1 + x2 falls short of 5 by 3, which the artificial variable of the first row makes up
+        assert!(matches!(
+            solve_task(task),
+            Err(SimplexMethodError::Infeasible { penalty }) if penalty == "3"
+        ));
+    }
+
+    #[test]
+    fn test_contradictory_bounds() {
+        let task: Task = "x1 <= 1\nx1 >= 2\nz = x1 -> max\nsolve using taxes"
+            .parse()
+            .unwrap();
+
+        // x1 stops at 1, a unit short of the second row
+        assert!(matches!(
+            solve_task(task),
+            Err(SimplexMethodError::Infeasible { penalty }) if penalty == "1"
+        ));
     }
 }
@@ -1,43 +1,47 @@
 use std::{
     clone::Clone,
-    collections::HashMap,
+    collections::BTreeMap,
     fmt::{Debug, Display},
     marker::PhantomData,
 };
 
 use ndarray::{aview0, Array1, Array2, Axis};
-use num::{traits::NumAssign, Num, Rational64, Zero};
+use num::{traits::NumAssign, Num, One, Rational64, Zero};
 
-use crate::tax_numbers::Tax;
+use crate::tax_numbers::{LiftRational, Tax};
 use crate::{
-    parser::{Goal, Relation, Task},
+    parser::{BoundKind, Goal, Method, Relation, Restriction, TargetFn, Task, Term},
     simplex::SimplexSolver,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct SimplexTerm<F: Debug> {
     coef: F,
     index: u64,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct SimplexRestriction<F: Debug> {
     terms: Vec<SimplexTerm<F>>,
     relation: Relation,
     free: F,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 struct SimplexTarget<F: Debug> {
     terms: Vec<SimplexTerm<F>>,
     free: F,
     goal: Goal,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct SimplexTask<F: Debug> {
     restrictions: Vec<SimplexRestriction<F>>,
     target_fn: SimplexTarget<F>,
+    /// Indices of the nonnegative and the nonpositive part of every free variable
+    splits: Vec<(u64, u64)>,
+    /// How many of the last restrictions are the rows of variable bounds
+    bound_rows: usize,
 }
 
 struct SimplexTaskParts<F: Debug> {
@@ -46,30 +50,92 @@ struct SimplexTaskParts<F: Debug> {
     z: Array1<F>,
 }
 
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub struct Simple;
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub struct Taxes;
+#[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub struct DoublePhase;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct CanonicSimplexTask<T: Debug, M> {
     task: SimplexTask<T>,
     max_index: u64,
+    /// Number of the decision variables, which come before the slack columns
+    variables: usize,
+    /// Column of the slack or surplus variable added to each restriction, if any
+    slacks: Vec<Option<usize>>,
+    /// Restrictions multiplied by -1 to get a nonnegative right-hand side
+    negated: Vec<bool>,
     phantom: PhantomData<M>,
 }
 
-impl<T: Debug + From<Rational64>> From<Task> for SimplexTask<T> {
-    fn from(value: Task) -> Self {
+impl<T: Debug + LiftRational> From<Task> for SimplexTask<T> {
+    fn from(mut value: Task) -> Self {
+        let bounds = std::mem::take(&mut value.bounds);
+        let user_rows = value.restrictions.len();
+        for bound in &bounds {
+            let (relation, limit) = match bound.kind {
+                BoundKind::Free => continue,
+                BoundKind::Upper(limit) => (Relation::Less, limit),
+                BoundKind::Lower(limit) => (Relation::Greater, limit),
+            };
+            value.restrictions.push(Restriction {
+                relation,
+                terms: vec![Term {
+                    coef: Rational64::one(),
+                    index: bound.index,
+                }],
+                value: limit,
+                section: None,
+            });
+        }
+
+        let bound_rows = value.restrictions.len() - user_rows;
+
+        // a free variable is written as `x - x'` with both parts nonnegative, `x'` taking a new
+        // column after every other variable. A negative lower bound replaces `x >= 0` as well,
+        // so the variable is split and only its own row keeps it from below.
+        let mut free = bounds
+            .iter()
+            .filter(|x| x.allows_negative())
+            .map(|x| x.index)
+            .collect::<Vec<_>>();
+        free.sort();
+        free.dedup();
+        let mut splits = vec![];
+        for index in free {
+            let negative = value.next_index();
+            let rows = value
+                .restrictions
+                .iter_mut()
+                .map(|x| &mut x.terms)
+                .chain([&mut value.target_fn.terms]);
+            for terms in rows {
+                let negated = terms
+                    .iter()
+                    .filter(|x| x.index == index)
+                    .map(|x| Term {
+                        coef: -x.coef,
+                        index: negative,
+                    })
+                    .collect::<Vec<_>>();
+                terms.extend(negated);
+            }
+            splits.push((index, negative));
+        }
+
         let restrictions = value
             .restrictions
             .into_iter()
             .map(|x| SimplexRestriction {
-                free: x.value.into(),
+                free: T::lift(x.value),
                 relation: x.relation,
                 terms: x
                     .terms
                     .into_iter()
                     .map(|y| SimplexTerm {
-                        coef: y.coef.into(),
+                        coef: T::lift(y.coef),
                         index: y.index,
                     })
                     .collect(),
@@ -77,13 +143,13 @@ impl<T: Debug + From<Rational64>> From<Task> for SimplexTask<T> {
             .collect();
 
         let target_fn = SimplexTarget {
-            free: value.target_fn.value.into(),
+            free: T::lift(value.target_fn.value),
             terms: value
                 .target_fn
                 .terms
                 .into_iter()
                 .map(|x| SimplexTerm {
-                    coef: x.coef.into(),
+                    coef: T::lift(x.coef),
                     index: x.index,
                 })
                 .collect(),
@@ -93,12 +159,215 @@ impl<T: Debug + From<Rational64>> From<Task> for SimplexTask<T> {
         Self {
             restrictions,
             target_fn,
+            splits,
+            bound_rows,
+        }
+    }
+}
+
+/// Assembles a task in code instead of parsing it, terms being given as `(index, coefficient)`
+/// pairs with variables counted from 1
+#[derive(Debug, Clone)]
+pub struct SimplexTaskBuilder {
+    goal: Goal,
+    objective: Vec<Term>,
+    restrictions: Vec<Restriction>,
+}
+
+fn terms(terms: &[(u64, Rational64)]) -> Vec<Term> {
+    terms
+        .iter()
+        .map(|&(index, coef)| Term { coef, index })
+        .collect()
+}
+
+impl SimplexTaskBuilder {
+    /// An empty task which maximizes
+    pub fn new() -> Self {
+        Self {
+            goal: Goal::Maximize,
+            objective: vec![],
+            restrictions: vec![],
+        }
+    }
+
+    pub fn maximize(mut self) -> Self {
+        self.goal = Goal::Maximize;
+        self
+    }
+
+    pub fn minimize(mut self) -> Self {
+        self.goal = Goal::Minimize;
+        self
+    }
+
+    pub fn objective(mut self, objective: &[(u64, Rational64)]) -> Self {
+        self.objective = terms(objective);
+        self
+    }
+
+    pub fn constraint(
+        mut self,
+        relation: Relation,
+        lhs: &[(u64, Rational64)],
+        rhs: Rational64,
+    ) -> Self {
+        self.restrictions.push(Restriction {
+            relation,
+            terms: terms(lhs),
+            value: rhs,
+            section: None,
+        });
+        self
+    }
+
+    /// Goes through the same conversion as a parsed [`Task`]
+    pub fn build<T: Debug + LiftRational>(self) -> SimplexTask<T> {
+        Task {
+            restrictions: self.restrictions,
+            target_fn: TargetFn {
+                goal: self.goal,
+                terms: self.objective,
+                value: Rational64::zero(),
+            },
+            bounds: vec![],
+            method: Method::Simple,
         }
+        .into()
+    }
+}
+
+impl Default for SimplexTaskBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What canonization adds to a task, see [`SimplexTask::describe`]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct TaskSummary {
+    pub variables: usize,
+    /// Restrictions by relation: `<=`, `=` and `>=`
+    pub relations: [usize; 3],
+    pub slacks: usize,
+    pub surpluses: usize,
+    pub artificials: usize,
+    /// Rows and columns of the starting tableau, the objective row and the free column included
+    pub shape: (usize, usize),
+}
+
+impl Display for TaskSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [less, equal, greater] = self.relations;
+        writeln!(f, "Variables: {}", self.variables)?;
+        writeln!(f, "Restrictions: {less} <=, {equal} =, {greater} >=")?;
+        writeln!(f, "Slacks added: {}", self.slacks)?;
+        writeln!(f, "Surpluses added: {}", self.surpluses)?;
+        writeln!(f, "Artificials added: {}", self.artificials)?;
+        writeln!(f, "Tableau: {} x {}", self.shape.0, self.shape.1)
     }
 }
 
 impl<T: Debug> SimplexTask<T> {
-    pub fn canonize<M>(mut self) -> CanonicSimplexTask<T, M>
+    pub fn canonize<M>(self) -> CanonicSimplexTask<T, M>
+    where
+        T: Num + NumAssign + PartialOrd,
+    {
+        self.standard_form().into_method()
+    }
+
+    pub fn goal(&self) -> &Goal {
+        &self.target_fn.goal
+    }
+
+    /// Adds the restriction `x{index} <relation> value`
+    pub fn bound_variable(&mut self, index: u64, relation: Relation, value: T)
+    where
+        T: One,
+    {
+        self.bound_rows += 1;
+        self.restrictions.push(SimplexRestriction {
+            terms: vec![SimplexTerm {
+                coef: T::one(),
+                index,
+            }],
+            relation,
+            free: value,
+        });
+    }
+
+    /// Whether `point`, holding the value of `x{i}` at `i - 1`, satisfies every restriction, the
+    /// rows of the bounds included, with every variable but the free ones nonnegative
+    pub fn holds_at(&self, point: &[T]) -> bool
+    where
+        T: Num + PartialOrd + Clone,
+    {
+        let columns = self
+            .restrictions
+            .iter()
+            .flat_map(|x| &x.terms)
+            .map(|x| x.index as usize)
+            .chain([point.len()])
+            .max()
+            .unwrap_or(0);
+        let mut values = vec![T::zero(); columns];
+        values[..point.len()].clone_from_slice(point);
+        // a negative free variable is carried by its nonpositive part
+        for &(positive, negative) in &self.splits {
+            let value = values[positive as usize - 1].clone();
+            if value < T::zero() {
+                values[positive as usize - 1] = T::zero();
+                values[negative as usize - 1] = T::zero() - value;
+            }
+        }
+
+        values.iter().all(|x| *x >= T::zero())
+            && self.restrictions.iter().all(|restriction| {
+                let lhs = restriction.terms.iter().fold(T::zero(), |acc, x| {
+                    acc + x.coef.clone() * values[x.index as usize - 1].clone()
+                });
+                match restriction.relation {
+                    Relation::Equal => lhs == restriction.free,
+                    Relation::Less => lhs <= restriction.free,
+                    Relation::Greater => lhs >= restriction.free,
+                }
+            })
+    }
+
+    /// Counts the variables canonization by `method` adds without solving the task. Both the
+    /// Big-M and the two-phase method start from an artificial basis of one variable per row.
+    pub fn describe(&self, method: Method) -> TaskSummary
+    where
+        T: Num + NumAssign + PartialOrd + Clone,
+    {
+        let count = |relation| {
+            self.restrictions
+                .iter()
+                .filter(|x| x.relation == relation)
+                .count()
+        };
+        let relations = [Relation::Less, Relation::Equal, Relation::Greater].map(count);
+        let canonic = self.clone().standard_form();
+        let rows = canonic.slacks.len();
+        let artificials = match method {
+            Method::Simple => 0,
+            Method::Taxes | Method::SecondPhase => rows,
+        };
+
+        TaskSummary {
+            variables: canonic.variables,
+            relations,
+            slacks: relations[0],
+            surpluses: relations[2],
+            artificials,
+            shape: (rows + 1, canonic.max_index as usize + artificials + 1),
+        }
+    }
+
+    /// Brings the task to the standard form every method starts from: each restriction gets its
+    /// slack or surplus variable and becomes an equality with a nonnegative right-hand side.
+    /// The method's own additions are made when converting to a [`SimplexSolver`].
+    pub fn standard_form(mut self) -> CanonicSimplexTask<T, ()>
     where
         T: Num + NumAssign + PartialOrd,
     {
@@ -106,9 +375,13 @@ impl<T: Debug> SimplexTask<T> {
             .restrictions
             .iter()
             .flat_map(|x| &x.terms)
-            .max_by_key(|x| x.index)
-            .unwrap()
-            .index;
+            .chain(&self.target_fn.terms)
+            .map(|x| x.index)
+            .max()
+            .unwrap_or(0);
+        let variables = max_index as usize;
+        let mut slacks = Vec::with_capacity(self.restrictions.len());
+        let mut negated = Vec::with_capacity(self.restrictions.len());
 
         for restriction in &mut self.restrictions {
             match restriction.relation {
@@ -117,20 +390,23 @@ impl<T: Debug> SimplexTask<T> {
                         coef: T::one(),
                         index: max_index + 1,
                     });
+                    slacks.push(Some(max_index as usize));
                     max_index += 1;
                 }
-                Relation::Equal => (),
+                Relation::Equal => slacks.push(None),
                 Relation::Greater => {
                     restriction.terms.push(SimplexTerm {
                         coef: T::zero() - T::one(),
                         index: max_index + 1,
                     });
+                    slacks.push(Some(max_index as usize));
                     max_index += 1;
                 }
             }
 
             restriction.relation = Relation::Equal;
 
+            negated.push(restriction.free < T::zero());
             if restriction.free < T::zero() {
                 restriction
                     .terms
@@ -142,106 +418,276 @@ impl<T: Debug> SimplexTask<T> {
         CanonicSimplexTask {
             task: self,
             max_index,
+            variables,
+            slacks,
+            negated,
             phantom: PhantomData
         }
     }
 }
 
 #[cfg(not(feature = "taxes"))]
-impl<F: Display + Num + Clone + Debug + Copy> From<CanonicSimplexTask<F, Simple>>
+impl<F: Display + Num + Clone + Debug> From<CanonicSimplexTask<F, Simple>>
     for SimplexSolver<F>
 {
     fn from(val: CanonicSimplexTask<F, Simple>) -> Self {
         let goal = val.task.target_fn.goal.clone();
+        let (slacks, variables, splits) = (val.slacks.clone(), val.variables, val.split_columns());
+        let (negated, bound_rows) = (val.negated.clone(), val.task.bound_rows);
 
         let mut parts = val.into_a_b_z();
+        // the row holds `z_j - c_j` whatever the goal, which only decides the sign of the reduced
+        // costs that improve it, so a minimized objective is never negated by hand
         parts.invert_z();
         let contents = parts.into_contents();
 
-        SimplexSolver::from_contents(contents, goal)
+        SimplexSolver::from_tableau(contents, goal)
+            .with_slacks(slacks)
+            .with_variables(variables)
+            .with_splits(splits)
+            .with_negated(negated)
+            .with_bound_rows(bound_rows)
+            .with_method(Method::Simple)
     }
 }
 
-impl<F: Display + Num + Clone + Debug + Copy> From<CanonicSimplexTask<Tax<F>, Taxes>>
+impl<F: Display + Num + Clone + Debug> From<CanonicSimplexTask<Tax<F>, Taxes>>
     for SimplexSolver<Tax<F>>
 {
     fn from(val: CanonicSimplexTask<Tax<F>, Taxes>) -> Self {
         let goal = val.task.target_fn.goal.clone();
+        let (slacks, variables, splits) = (val.slacks.clone(), val.variables, val.split_columns());
+        let (negated, bound_rows) = (val.negated.clone(), val.task.bound_rows);
         let mut parts = val.into_a_b_z();
-        parts.add_taxes();
+        parts.add_taxes(&goal);
         parts.add_basis();
         parts.invert_z();
         let contents = parts.into_contents();
 
-        SimplexSolver::from_contents(contents, goal)
+        // after inversion every right-hand side is taxed against the goal
+        let tax = Tax::from((F::zero(), F::one()));
+        let weight = match goal {
+            Goal::Maximize => Tax::zero() - tax,
+            Goal::Minimize => tax,
+        };
+        let rows = contents.len_of(Axis(0)) - 1;
+        let rhs_weights = Array1::from_elem(rows, weight);
+        // every restriction got an artificial variable, in the columns right before the free one
+        let columns = contents.len_of(Axis(1)) - 1;
+        let artificials = (columns - rows..columns).collect();
+
+        SimplexSolver::from_tableau(contents, goal)
+            .with_slacks(slacks)
+            .with_variables(variables)
+            .with_splits(splits)
+            .with_negated(negated)
+            .with_bound_rows(bound_rows)
+            .with_rhs_weights(rhs_weights)
+            .with_artificials(artificials)
+            .with_method(Method::Taxes)
     }
 }
 
-impl<F: Display + Num + Clone + Debug + Copy> From<CanonicSimplexTask<F, DoublePhase>>
+impl<F: Display + Num + Clone + Debug> From<CanonicSimplexTask<F, DoublePhase>>
     for SimplexSolver<F>
 {
     fn from(val: CanonicSimplexTask<F, DoublePhase>) -> Self {
         let goal = val.task.target_fn.goal.clone();
+        let (slacks, variables, splits) = (val.slacks.clone(), val.variables, val.split_columns());
+        let (negated, bound_rows) = (val.negated.clone(), val.task.bound_rows);
         let mut parts = val.into_a_b_z();
         parts.add_basis();
         parts.invert_z();
         let contents = parts.into_contents();
 
-        SimplexSolver::from_contents(contents, goal)
+        SimplexSolver::from_tableau(contents, goal)
+            .with_slacks(slacks)
+            .with_variables(variables)
+            .with_splits(splits)
+            .with_negated(negated)
+            .with_bound_rows(bound_rows)
+            .with_method(Method::SecondPhase)
+    }
+}
+
+impl<T: Debug> CanonicSimplexTask<T, ()> {
+    /// Picks the method to solve the standard form with
+    pub fn into_method<M>(self) -> CanonicSimplexTask<T, M> {
+        CanonicSimplexTask {
+            task: self.task,
+            max_index: self.max_index,
+            variables: self.variables,
+            slacks: self.slacks,
+            negated: self.negated,
+            phantom: PhantomData,
+        }
     }
 }
 
 impl<T: Debug, M> CanonicSimplexTask<T, M> {
+    /// Columns of the two parts of every free variable
+    fn split_columns(&self) -> Vec<(usize, usize)> {
+        self.task
+            .splits
+            .iter()
+            .map(|&(positive, negative)| (positive as usize - 1, negative as usize - 1))
+            .collect()
+    }
+
     fn into_a_b_z(self) -> SimplexTaskParts<T>
     where
-        T: Copy + Zero,
+        T: Clone + Num,
     {
         let restrictions_len = self.task.restrictions.len();
 
-        let mut a_hash_map = self
-            .task
-            .restrictions
-            .iter()
-            .map(|x| {
-                x.terms
-                    .iter()
-                    .map(|y| ((y.index - 1) as usize, y.coef))
-                    .collect::<HashMap<_, _>>()
-            })
-            .enumerate()
-            .collect::<HashMap<_, _>>();
+        // every coefficient is written straight to its column, so the order of terms within a
+        // restriction never matters
+        let mut a = Array2::from_elem((restrictions_len, self.max_index as usize), T::zero());
+        for (i, restriction) in self.task.restrictions.iter().enumerate() {
+            for term in &restriction.terms {
+                a[[i, (term.index - 1) as usize]] = term.coef.clone();
+            }
+        }
 
-        let mut z_hash_map = self
-            .task
-            .target_fn
-            .terms
-            .into_iter()
-            .map(|x| ((x.index - 1) as usize, x.coef))
-            .collect::<HashMap<_, _>>();
-
-        let a = Array2::from_shape_fn((restrictions_len, self.max_index as usize), |(i, j)| {
-            *a_hash_map
-                .entry(i)
-                .or_insert(HashMap::new())
-                .entry(j)
-                .or_insert(T::zero())
-        });
         let b = Array1::from_shape_vec(
             restrictions_len,
             self.task.restrictions.into_iter().map(|x| x.free).collect(),
         )
         .unwrap();
-        let mut z = Array1::from_shape_fn(self.max_index as usize, |i| {
-            *z_hash_map.entry(i).or_insert(T::zero())
-        });
-        z.push(Axis(0), aview0(&self.task.target_fn.free)).unwrap();
+        let mut z = Array1::from_elem(self.max_index as usize, T::zero());
+        for term in self.task.target_fn.terms {
+            z[(term.index - 1) as usize] = term.coef;
+        }
+        // the objective row reads `z - c*x = free` and is negated as a whole by `invert_z`
+        let free = T::zero() - self.task.target_fn.free;
+        z.push(Axis(0), aview0(&free)).unwrap();
 
         SimplexTaskParts { a, b, z }
     }
 }
 
+/// Prints the equalities the solver starts from, one per line followed by the objective. The
+/// slack or surplus column of the `k`-th restriction is named `s{k}`, the other columns keep
+/// their `x{i}` names.
+impl<T: Debug + Display + Num + PartialOrd + Clone, M> Display for CanonicSimplexTask<T, M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = |index: u64| {
+            let column = Some(index as usize - 1);
+            match self.slacks.iter().position(|x| *x == column) {
+                Some(k) => format!("s{}", k + 1),
+                None => format!("x{index}"),
+            }
+        };
+        let sum = |terms: &[SimplexTerm<T>]| {
+            let mut terms: Vec<_> = terms.iter().filter(|x| !x.coef.is_zero()).collect();
+            terms.sort_by_key(|x| x.index);
+            let mut out = String::new();
+            for (i, term) in terms.into_iter().enumerate() {
+                let (sign, coef) = if term.coef < T::zero() {
+                    ("-", T::zero() - term.coef.clone())
+                } else {
+                    ("+", term.coef.clone())
+                };
+                let coef = if coef.is_one() {
+                    String::new()
+                } else {
+                    coef.to_string()
+                };
+                out += &match (i, sign) {
+                    (0, "+") => format!("{coef}{}", name(term.index)),
+                    (0, _) => format!("-{coef}{}", name(term.index)),
+                    _ => format!(" {sign} {coef}{}", name(term.index)),
+                };
+            }
+            if out.is_empty() {
+                out.push('0');
+            }
+            out
+        };
+
+        for restriction in &self.task.restrictions {
+            writeln!(f, "{} = {}", sum(&restriction.terms), restriction.free)?;
+        }
+        let target = &self.task.target_fn;
+        let free = if target.free.is_zero() {
+            String::new()
+        } else if target.free < T::zero() {
+            format!(" - {}", T::zero() - target.free.clone())
+        } else {
+            format!(" + {}", target.free)
+        };
+        let goal = match target.goal {
+            Goal::Maximize => "max",
+            Goal::Minimize => "min",
+        };
+        writeln!(f, "z = {}{free} -> {goal}", sum(&target.terms))
+    }
+}
+
+impl<M> CanonicSimplexTask<Rational64, M> {
+    /// Writes the canonical task, slacks included, in free MPS format for cross-checking the
+    /// optimum with another solver. Every restriction is an equality `R{i}` and the objective is
+    /// the `COST` row, variables keep their default non-negative bounds. Fractions are written
+    /// as decimals, since MPS has no notation for them.
+    pub fn to_mps(&self) -> String {
+        let number = |x: &Rational64| {
+            if x.is_integer() {
+                x.to_string()
+            } else {
+                (*x.numer() as f64 / *x.denom() as f64).to_string()
+            }
+        };
+        let sense = match self.task.target_fn.goal {
+            Goal::Maximize => "MAX",
+            Goal::Minimize => "MIN",
+        };
+
+        let mut columns = BTreeMap::<u64, Vec<(String, &Rational64)>>::new();
+        for term in &self.task.target_fn.terms {
+            columns
+                .entry(term.index)
+                .or_default()
+                .push(("COST".to_owned(), &term.coef));
+        }
+        for (i, restriction) in self.task.restrictions.iter().enumerate() {
+            for term in &restriction.terms {
+                columns
+                    .entry(term.index)
+                    .or_default()
+                    .push((format!("R{}", i + 1), &term.coef));
+            }
+        }
+
+        let mut mps = format!("NAME SIMPLEX\nOBJSENSE\n    {sense}\nROWS\n N  COST\n");
+        for i in 1..=self.task.restrictions.len() {
+            mps += &format!(" E  R{i}\n");
+        }
+        mps += "COLUMNS\n";
+        for (index, entries) in columns {
+            for (row, coef) in entries.into_iter().filter(|(_, x)| !x.is_zero()) {
+                mps += &format!("    X{index} {row} {}\n", number(coef));
+            }
+        }
+        mps += "RHS\n";
+        for (i, restriction) in self.task.restrictions.iter().enumerate() {
+            if !restriction.free.is_zero() {
+                mps += &format!("    RHS R{} {}\n", i + 1, number(&restriction.free));
+            }
+        }
+        // the right-hand side of the objective row is the negated objective constant
+        let free = self.task.target_fn.free;
+        if !free.is_zero() {
+            mps += &format!("    RHS COST {}\n", number(&-free));
+        }
+        mps += "ENDATA\n";
+
+        mps
+    }
+}
+
 impl<T: Debug + Display + Num + Clone> SimplexTaskParts<Tax<T>> {
-    fn add_taxes(&mut self)
+    /// Penalizes the artificial basis with `M`, which works against the goal
+    fn add_taxes(&mut self, goal: &Goal)
     where
         T: Num + Clone + Display,
     {
@@ -250,8 +696,14 @@ impl<T: Debug + Display + Num + Clone> SimplexTaskParts<Tax<T>> {
             .push(Axis(0), aview0(&self.b.sum().into_tax()))
             .unwrap();
 
-        self.z
-            .zip_mut_with(&taxed, |x, y| *x = x.clone() + y.clone())
+        match goal {
+            Goal::Maximize => self
+                .z
+                .zip_mut_with(&taxed, |x, y| *x = x.clone() + y.clone()),
+            Goal::Minimize => self
+                .z
+                .zip_mut_with(&taxed, |x, y| *x = x.clone() - y.clone()),
+        }
     }
 }
 
@@ -292,3 +744,436 @@ impl<T: Debug> SimplexTaskParts<T> {
         self.a
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use num::{BigRational, Rational64};
+
+    use crate::{
+        errors::SimplexMethodError,
+        parser::{Goal, Method, ParserConfig, Relation, Restriction, TargetFn, Task, Term},
+        simplex::SimplexSolver,
+        task::{DoublePhase, Simple, SimplexTask, SimplexTaskBuilder, TaskSummary, Taxes},
+        tax_numbers::Tax,
+    };
+
+    #[test]
+    fn test_piecewise_cost() {
+        // profit 5/2 per unit against a convex cost with slopes 2 and 3
+        let task: Task = "x1 <= 10\nz = 2.5x1 -> max\npwl x1: (0,0) (5,-10) (10,-25)"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        assert!(solution.to_string().contains("Optimal z is: 5/2\n"));
+    }
+
+    #[test]
+    fn test_soft_constraint() {
+        // each unit past 4 earns 1 and costs 1/2, so x1 goes up to the hard limit
+        let task: Task = "x1 <= 10\nsoft c1: x1 <= 4 penalty 0.5\nz = x1 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        let solution = solver.solve().unwrap();
+
+        assert!(solution.to_string().contains("Optimal z is: 7\n"));
+        assert!(solution.to_string().contains("   x2 = 6\n"));
+    }
+
+    #[test]
+    fn test_negative_maximum() {
+        // the free term is negative at the optimum, which must not count as a reduced cost
+        let task: Task = "x1 + x2 >= 2\nz = -x1 + -3x2 -> max".parse().unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        assert!(solution.to_string().contains("Optimal z is: -2\n"));
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let task: Task = "x1 + x3 <= 4\nx3 >= 2\nx1 >= 0\nz = 3x1 + x3 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        assert!(solution.to_string().contains("Optimal z is: 8\n"));
+    }
+
+    #[test]
+    fn test_substitute_singletons() {
+        let mut task: Task = "x1 + x2 + x3 <= 10\nx3 == 4\nz = x1 + 2x2 + 3x3 -> max"
+            .parse()
+            .unwrap();
+        let fixed = task.substitute_singletons();
+        assert_eq!(fixed, vec![(3, 4.into())]);
+        assert_eq!(task.restrictions.len(), 1);
+
+        let fixed = vec![(2, Tax::from(Rational64::from(4)))];
+        let simple: SimplexTask<Tax<Rational64>> = task.clone().into();
+        let taxes: SimplexTask<Tax<Rational64>> = task.into();
+        for solution in [
+            SimplexSolver::from(simple.canonize::<Simple>()).solve(),
+            SimplexSolver::from(taxes.canonize::<Taxes>()).solve(),
+        ] {
+            let solution = solution.unwrap().with_fixed(fixed.clone()).to_string();
+
+            // the substituted 3x3 stays in the objective as a constant
+            assert!(solution.contains("Optimal z is: 24\n"));
+            assert!(solution.contains("   x3 = 4\n"));
+        }
+    }
+
+    #[test]
+    fn test_to_mps() {
+        let task: Task = "x1 + x2 <= 4\nx1 - x2 >= -1\nz = 1.5x1 + 2x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+
+        assert_eq!(
+            task.canonize::<Simple>().to_mps(),
+            "NAME SIMPLEX\nOBJSENSE\n    MAX\nROWS\n N  COST\n E  R1\n E  R2\nCOLUMNS\n\
+             \x20   X1 COST 1.5\n    X1 R1 1\n    X1 R2 -1\n\
+             \x20   X2 COST 2\n    X2 R1 1\n    X2 R2 1\n\
+             \x20   X3 R1 1\n    X4 R2 1\n\
+             RHS\n    RHS R1 4\n    RHS R2 1\nENDATA\n"
+        );
+    }
+
+    #[test]
+    fn test_display_canonical() {
+        let task: Task = "x1 + x2 <= 4\nx1 - x2 >= -1\n2x1 + x2 == 6\nz = 1.5x1 + 2x2 + 1 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+
+        // the second row is negated to get a nonnegative right-hand side
+        assert_eq!(
+            task.canonize::<Simple>().to_string(),
+            "x1 + x2 + s1 = 4\n-x1 + x2 + s2 = 1\n2x1 + x2 = 6\nz = 3/2x1 + 2x2 + 1 -> max\n"
+        );
+
+        // the equality has no slack, so the one of the third restriction is still `s3`
+        let task: Task = "2x1 + x2 == 6\nx1 + x2 <= 4\nx1 - x2 >= -1\nz = x1 + x2 - 1 -> min"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+        assert_eq!(
+            task.canonize::<Simple>().to_string(),
+            "2x1 + x2 = 6\nx1 + x2 + s2 = 4\n-x1 + x2 + s3 = 1\nz = x1 + x2 - 1 -> min\n"
+        );
+    }
+
+    #[test]
+    fn test_method() {
+        let task = || -> SimplexTask<Tax<Rational64>> {
+            let task: Task = "x1 + x2 <= 4\nz = x1 -> max".parse().unwrap();
+            task.into()
+        };
+
+        let simple = SimplexSolver::from(task().canonize::<Simple>())
+            .solve()
+            .unwrap();
+        let taxes = SimplexSolver::from(task().canonize::<Taxes>())
+            .solve()
+            .unwrap();
+        let second_phase = SimplexSolver::from(task().canonize::<DoublePhase>())
+            .solve()
+            .unwrap();
+
+        assert_eq!(simple.method(), Some(Method::Simple));
+        assert_eq!(taxes.method(), Some(Method::Taxes));
+        assert_eq!(second_phase.method(), Some(Method::SecondPhase));
+        assert!(taxes.to_string().contains("Solved via: Big-M\n"));
+    }
+
+    #[test]
+    fn test_standard_form() {
+        let task: Task = "x1 + x2 <= 4\nx1 <= 3\nz = 2x1 + x2 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let standard = task.standard_form();
+
+        let simple = SimplexSolver::from(standard.clone().into_method::<Simple>());
+        let taxes = SimplexSolver::from(standard.into_method::<Taxes>());
+
+        for solution in [simple.solve().unwrap(), taxes.solve().unwrap()] {
+            assert!(solution.to_string().contains("Optimal z is: 7\n"));
+        }
+    }
+
+    #[test]
+    fn test_restriction_constant() {
+        let parts = |s: &str| {
+            let task: Task = s.parse().unwrap();
+            let task: SimplexTask<Rational64> = task.into();
+            task.canonize::<Simple>().into_a_b_z()
+        };
+
+        let moved = parts("x1 + 2 <= 5\n1 - x2 >= -3\nz = x1 + x2 -> max");
+        let folded = parts("x1 <= 3\n-x2 >= -4\nz = x1 + x2 -> max");
+
+        assert_eq!(moved.a, folded.a);
+        assert_eq!(moved.b, folded.b);
+    }
+
+    #[test]
+    fn test_term_order() {
+        let rows = |s: &str| {
+            let task: Task = s.parse().unwrap();
+            let task: SimplexTask<Rational64> = task.into();
+            task.canonize::<Simple>().into_a_b_z()
+        };
+
+        let shuffled = rows("x3 + 2x1 <= 4\nx2 + x1 <= 2\nz = x3 + x1 -> max");
+        let ordered = rows("2x1 + x3 <= 4\nx1 + x2 <= 2\nz = x1 + x3 -> max");
+
+        assert_eq!(shuffled.a, ordered.a);
+        assert_eq!(shuffled.z, ordered.z);
+    }
+
+    #[test]
+    fn test_index_base_labels() {
+        let config = ParserConfig { index_base: 0 };
+        let task = Task::parse_with("x0 + x1 <= 4\nx0 <= 3\nz = 2x0 + x1 -> max", config).unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        let solution = solver.solve().unwrap().with_index_base(0).to_string();
+
+        assert!(solution.contains("   x0 = 3\n"));
+        assert!(solution.contains("   x1 = 1\n"));
+    }
+
+    #[test]
+    fn test_objective_constant() {
+        let value = |goal: &str| {
+            let task: Task = format!("x1 + x2 <= 2\nx1 <= 1\nz = x1 - 3x2 + 5 - 1.5 -> {goal}")
+                .parse()
+                .unwrap();
+            let task: SimplexTask<Tax<Rational64>> = task.into();
+            let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+            solver.solve().unwrap().objective_value()
+        };
+
+        // the constant shifts both optima of test_objective_value by 7/2
+        assert_eq!(value("min"), Tax::from(Rational64::new(-5, 2)));
+        assert_eq!(value("max"), Tax::from(Rational64::new(9, 2)));
+    }
+
+    #[test]
+    fn test_builder() {
+        let r = Rational64::from;
+        let parsed: Task = "x1 + x2 <= 4\n2x1 >= 1\nz = 2x1 + 3x2 -> min"
+            .parse()
+            .unwrap();
+
+        let built = SimplexTaskBuilder::new()
+            .minimize()
+            .objective(&[(1, r(2)), (2, r(3))])
+            .constraint(Relation::Less, &[(1, r(1)), (2, r(1))], r(4))
+            .constraint(Relation::Greater, &[(1, r(2))], r(1))
+            .build::<Tax<Rational64>>();
+
+        assert_eq!(built, SimplexTask::from(parsed));
+    }
+
+    #[test]
+    fn test_describe() {
+        let task: Task = "x1 + x2 <= 4\nx1 - x3 >= 1\nx2 + x3 == 2\nz = x1 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Rational64> = task.into();
+
+        assert_eq!(
+            task.describe(Method::Taxes),
+            TaskSummary {
+                variables: 3,
+                relations: [1, 1, 1],
+                slacks: 1,
+                surpluses: 1,
+                artificials: 3,
+                shape: (4, 9),
+            }
+        );
+        assert_eq!(task.describe(Method::Simple).shape, (4, 6));
+    }
+
+    #[test]
+    fn test_free_variable() {
+        let task: Task = "x1 + x2 <= 2\nx2 <= 5\nz = x2 -> max\nx1 free\nx1 >= -3"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        // x2 only reaches 5 with x1 at its lowest, below zero
+        let value = |x: i64| Tax::from(Rational64::from(x));
+        assert_eq!(
+            solution.variable_values(),
+            HashMap::from([(1, value(-3)), (2, value(5))])
+        );
+        assert!(solution
+            .to_string()
+            .contains("Free variables are equal to: \n   x1 = -3\n"));
+    }
+
+    #[test]
+    fn test_negative_lower_bound() {
+        let task: Task = "x1 + x2 <= 2\nx2 <= 5\nz = x2 -> max\nx1 >= -3"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        // the bound replaces `x1 >= 0` instead of adding to it
+        assert_eq!(
+            solution.variable_values()[&1],
+            Tax::from(Rational64::from(-3))
+        );
+        assert!(solution.to_string().starts_with("Optimal z is: 5\n"));
+    }
+
+    #[test]
+    fn test_allocation() {
+        let task: Task = "simplex x1 x2 x3\nx1 <= 0.5\nz = 3x1 + 2x2 + x3 -> max"
+            .parse()
+            .unwrap();
+        let task: SimplexTask<Tax<Rational64>> = task.into();
+        let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+        let solution = solver.solve().unwrap();
+
+        // the most profitable share is capped at a half, the next one takes the rest
+        let values = solution.variable_values();
+        assert_eq!(values[&1], Tax::from(Rational64::new(1, 2)));
+        assert_eq!(
+            values[&1] + values[&2] + values[&3],
+            Tax::from(Rational64::from(1))
+        );
+    }
+
+    #[test]
+    fn test_bounding_box() {
+        let bounding_box = |input: &str| {
+            let task: Task = input.parse().unwrap();
+            let task: SimplexTask<Tax<Rational64>> = task.into();
+            SimplexSolver::from(task.canonize::<Taxes>()).bounding_box()
+        };
+        // a hexagon cut off the square [0, 4] x [0, 4], reaching x1 = 1/2 at x2 = 5/2 and back
+        let polygon = "x1 + x2 >= 3\nx1 <= 4\nx2 <= 4\nx1 - x2 <= 2\nx2 - x1 <= 2";
+        let range = (Rational64::new(1, 2), Rational64::from(4));
+
+        for goal in ["max", "min"] {
+            assert_eq!(
+                bounding_box(&format!("{polygon}\nz = x1 + 2x2 -> {goal}")),
+                Some([range, range])
+            );
+        }
+        assert_eq!(bounding_box("x1 + x2 + x3 <= 1\nz = x1 -> max"), None);
+        assert_eq!(bounding_box("x1 - x2 <= 1\nz = x1 + x2 -> min"), None);
+    }
+
+    #[test]
+    fn test_hash() {
+        let goals = HashSet::from([Goal::Maximize, Goal::Minimize, Goal::Maximize]);
+        let relations = HashSet::from([Relation::Equal, Relation::Less, Relation::Greater]);
+
+        assert_eq!(goals.len(), 2);
+        assert_eq!(relations.len(), 3);
+        assert_eq!(HashSet::from([Simple, Simple]).len(), 1);
+        assert_eq!(HashSet::from([Taxes]).len(), 1);
+        assert!(HashSet::from([DoublePhase]).contains(&DoublePhase));
+    }
+
+    /// Two restrictions with coprime denominators close to `2^32`, so the pivots multiply them
+    /// into values past `i64::MAX`
+    fn overflowing_task() -> Task {
+        let (p, q) = (4_294_967_291, 4_294_967_279);
+        let restriction = |a, b| Restriction {
+            relation: Relation::Less,
+            terms: vec![
+                Term {
+                    coef: Rational64::new(1, a),
+                    index: 1,
+                },
+                Term {
+                    coef: Rational64::new(1, b),
+                    index: 2,
+                },
+            ],
+            value: 1.into(),
+            section: None,
+        };
+
+        Task {
+            restrictions: vec![restriction(p, q), restriction(q, p)],
+            target_fn: TargetFn {
+                goal: Goal::Maximize,
+                terms: vec![
+                    Term {
+                        coef: 1.into(),
+                        index: 1,
+                    },
+                    Term {
+                        coef: 1.into(),
+                        index: 2,
+                    },
+                ],
+                value: Default::default(),
+            },
+            bounds: vec![],
+            method: Method::Simple,
+        }
+    }
+
+    #[test]
+    fn test_bignum_avoids_overflow() {
+        let task: SimplexTask<Tax<BigRational>> = overflowing_task().into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+        let solution = solver.solve().unwrap();
+
+        // x1 = x2 = pq / (p + q), the optimum itself is already past `i64::MAX`
+        assert!(solution
+            .to_string()
+            .contains("Optimal z is: 18446743979220271189/4294967285\n"));
+    }
+
+    #[test]
+    fn test_rational_overflow_is_reported() {
+        let task: SimplexTask<Tax<Rational64>> = overflowing_task().into();
+        let solver: SimplexSolver<_> = task.canonize::<Simple>().into();
+
+        assert!(matches!(solver.solve(), Err(SimplexMethodError::Overflow)));
+    }
+
+    #[test]
+    fn test_fix_variable() {
+        let solve = |task: Task| {
+            let task: SimplexTask<Tax<Rational64>> = task.into();
+            let solver: SimplexSolver<_> = task.canonize::<Taxes>().into();
+            solver.solve().unwrap().to_string()
+        };
+        let mut task: Task = "x1 <= 4\nx2 <= 3\nx1 + x2 <= 5\nz = 2x1 + x2 -> max"
+            .parse()
+            .unwrap();
+
+        assert!(solve(task.clone()).contains("Optimal z is: 9\n"));
+
+        task.fix_variable(2, 3.into());
+        let fixed = solve(task);
+        assert!(fixed.contains("Optimal z is: 7\n"));
+        assert!(fixed.contains("x2 = 3\n"));
+    }
+}
@@ -4,7 +4,13 @@ use std::{
     str::FromStr,
 };
 
-use num::{traits::NumAssign, Complex, Num, One, Zero};
+use num::{
+    traits::NumAssign, BigRational, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Complex, Num,
+    One, Rational64, Zero,
+};
+
+/// Spellings of `M` itself, accepted by both [`FromStr`] and [`Num::from_str_radix`]
+pub const BIG_M_SYMBOLS: &[&str] = &["M", "BigM", "∞"];
 
 #[derive(PartialEq, Clone, Copy, Eq)]
 pub struct Tax<T>(Complex<T>); // T + T * M
@@ -13,6 +19,19 @@ impl<T> Tax<T> {
     pub fn into_tax(self) -> Tax<T> where T: Zero {
         Tax(Complex { re: T::zero(), im: self.0.re })
     }
+
+    /// The same `M` part with `re` as the plain number
+    pub fn with_real(self, re: T) -> Tax<T> {
+        Tax(Complex { re, im: self.0.im })
+    }
+
+    /// The plain number, if there is no `M` part
+    pub fn real(self) -> Option<T>
+    where
+        T: Zero,
+    {
+        self.0.im.is_zero().then_some(self.0.re)
+    }
 }
 
 impl<T: PartialOrd> PartialOrd for Tax<T> {
@@ -47,12 +66,50 @@ impl<T: Zero> From<T> for Tax<T> {
     }
 }
 
+/// Lifts a parsed coefficient into `r + 0M`: the rational becomes the real part (`re`) and the
+/// `M` part (`im`) stays zero, as the parser never produces coefficients of `M` itself
+pub fn lift_rational(r: Rational64) -> Tax<Rational64> {
+    Tax(Complex {
+        re: r,
+        im: Rational64::zero(),
+    })
+}
+
+/// Numbers a parsed task is solved in, made from its `Rational64` coefficients
+pub trait LiftRational {
+    fn lift(r: Rational64) -> Self;
+}
+
+impl LiftRational for Rational64 {
+    fn lift(r: Rational64) -> Self {
+        r
+    }
+}
+
+impl LiftRational for Tax<Rational64> {
+    fn lift(r: Rational64) -> Self {
+        lift_rational(r)
+    }
+}
+
+impl LiftRational for Tax<BigRational> {
+    fn lift(r: Rational64) -> Self {
+        r.into()
+    }
+}
+
+impl From<Rational64> for Tax<BigRational> {
+    fn from(value: Rational64) -> Self {
+        BigRational::new((*value.numer()).into(), (*value.denom()).into()).into()
+    }
+}
+
 impl<T: FromStr + One + Zero> FromStr for Tax<T> {
     type Err = T::Err;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
-            "M" => Ok((T::zero(), T::one()).into()),
+            _ if BIG_M_SYMBOLS.contains(&s) => Ok((T::zero(), T::one()).into()),
             _ => Ok((s.parse()?, T::zero()).into()),
         }
     }
@@ -91,6 +148,51 @@ functor_like_self_impl!(SubAssign, sub_assign);
 functor_like_self_impl!(DivAssign, div_assign);
 functor_like_self_impl!(RemAssign, rem_assign);
 
+impl<T: Num + Clone + CheckedAdd> CheckedAdd for Tax<T> {
+    fn checked_add(&self, v: &Self) -> Option<Self> {
+        Some(Tax(Complex::new(
+            self.0.re.checked_add(&v.0.re)?,
+            self.0.im.checked_add(&v.0.im)?,
+        )))
+    }
+}
+
+impl<T: Num + Clone + CheckedSub> CheckedSub for Tax<T> {
+    fn checked_sub(&self, v: &Self) -> Option<Self> {
+        Some(Tax(Complex::new(
+            self.0.re.checked_sub(&v.0.re)?,
+            self.0.im.checked_sub(&v.0.im)?,
+        )))
+    }
+}
+
+/// Mirrors `Complex` multiplication
+impl<T: Num + Clone + CheckedAdd + CheckedSub + CheckedMul> CheckedMul for Tax<T> {
+    fn checked_mul(&self, v: &Self) -> Option<Self> {
+        let (a, b, c, d) = (&self.0.re, &self.0.im, &v.0.re, &v.0.im);
+
+        Some(Tax(Complex::new(
+            a.checked_mul(c)?.checked_sub(&b.checked_mul(d)?)?,
+            a.checked_mul(d)?.checked_add(&b.checked_mul(c)?)?,
+        )))
+    }
+}
+
+/// Mirrors `Complex` division
+impl<T: Num + Clone + CheckedAdd + CheckedSub + CheckedMul + CheckedDiv> CheckedDiv for Tax<T> {
+    fn checked_div(&self, v: &Self) -> Option<Self> {
+        let (a, b, c, d) = (&self.0.re, &self.0.im, &v.0.re, &v.0.im);
+        let norm_sqr = c.checked_mul(c)?.checked_add(&d.checked_mul(d)?)?;
+        let re = a.checked_mul(c)?.checked_add(&b.checked_mul(d)?)?;
+        let im = b.checked_mul(c)?.checked_sub(&a.checked_mul(d)?)?;
+
+        Some(Tax(Complex::new(
+            re.checked_div(&norm_sqr)?,
+            im.checked_div(&norm_sqr)?,
+        )))
+    }
+}
+
 impl<T: num::Num + std::clone::Clone> One for Tax<T> {
     fn one() -> Self {
         (T::one(), T::zero()).into()
@@ -112,7 +214,7 @@ impl<T: Num + std::clone::Clone> Num for Tax<T> {
 
     fn from_str_radix(str: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
         Ok(match str {
-            "M" => (T::zero(), T::one()).into(),
+            _ if BIG_M_SYMBOLS.contains(&str) => (T::zero(), T::one()).into(),
             _ => (T::from_str_radix(str, radix)?, T::zero()).into(),
         })
     }
@@ -145,3 +247,32 @@ impl<T: Display + Num + Clone> Debug for Tax<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use num::{Num, Rational64};
+
+    use crate::tax_numbers::{lift_rational, Tax, BIG_M_SYMBOLS};
+
+    #[test]
+    fn test_lift_rational() {
+        assert_eq!(
+            lift_rational(3.into()),
+            Tax::from((3.into(), Rational64::from(0)))
+        );
+    }
+
+    #[test]
+    fn test_big_m_symbols() {
+        let m = Tax::from((Rational64::from(0), Rational64::from(1)));
+
+        for symbol in BIG_M_SYMBOLS {
+            assert_eq!(symbol.parse::<Tax<Rational64>>(), Ok(m));
+            assert_eq!(Tax::<Rational64>::from_str_radix(symbol, 10), Ok(m));
+        }
+        assert_eq!(
+            "7".parse::<Tax<Rational64>>(),
+            Ok(Rational64::from(7).into())
+        );
+    }
+}
@@ -0,0 +1,30 @@
+use std::{fs::write, path::Path, process::Command};
+
+#[test]
+fn test_describe() {
+    let input = Path::new(env!("CARGO_TARGET_TMPDIR")).join("describe.txt");
+    write(
+        &input,
+        "x1 + x2 <= 4\nx1 - x3 >= 1\nx2 + 2x3 <= 6\nx2 + x3 == 2\nz = x1 -> max\nsolve using taxes\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_simplex"))
+        .arg("--describe")
+        .arg(&input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = String::from_utf8(output.stdout).unwrap();
+
+    // taxes give every row an artificial variable
+    assert_eq!(
+        output,
+        "Variables: 3\n\
+         Restrictions: 2 <=, 1 =, 1 >=\n\
+         Slacks added: 2\n\
+         Surpluses added: 1\n\
+         Artificials added: 4\n\
+         Tableau: 5 x 11\n"
+    );
+}
@@ -0,0 +1,18 @@
+#![cfg(feature = "gzip")]
+
+use std::process::Command;
+
+fn run(path: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_simplex"))
+        .arg(path)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_gzip_input() {
+    assert_eq!(run("tests/input.txt.gz"), run("input.txt"));
+}
@@ -0,0 +1,67 @@
+use std::process::Command;
+
+fn run(style: &str) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_simplex"))
+        .args(["--style", style, "input.txt"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_solution_only() {
+    let output = run("solution");
+
+    assert!(output.starts_with("Optimal z is: "));
+    assert!(!output.contains("Basic: "));
+}
+
+#[test]
+fn test_tableaux() {
+    let output = run("tableaux");
+
+    assert!(output.contains("Basic: "));
+    assert!(!output.contains("Optimal z is: "));
+}
+
+#[test]
+fn test_both() {
+    let output = run("both");
+    let tableaux = output.find("Basic: ").unwrap();
+    let solution = output.find("Optimal z is: ").unwrap();
+
+    assert!(tableaux < solution);
+}
+
+#[test]
+fn test_unknown_style() {
+    let output = Command::new(env!("CARGO_BIN_EXE_simplex"))
+        .args(["--style", "loud", "input.txt"])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+}
+
+#[test]
+fn test_pretty() {
+    let output = Command::new(env!("CARGO_BIN_EXE_simplex"))
+        .args(["--pretty", "input.txt"])
+        .output()
+        .unwrap();
+    let output = String::from_utf8(output.stdout).unwrap();
+    let (tableaux, solution) = output.split_once("\n\n").unwrap();
+    let (initial, last) = tableaux.split_once("\n-").unwrap();
+    let last = last.split_once('\n').unwrap().1;
+
+    // 5 restrictions over 6 variables and 5 artificial ones, plus the header and the z row
+    for tableau in [initial, last] {
+        let rows = tableau.lines().collect::<Vec<_>>();
+        assert_eq!(rows.len(), 1 + 5 + 1);
+        assert!(rows.iter().all(|x| x.split('|').count() == 1 + 11 + 1));
+        assert!(rows[0].trim_start().starts_with("basis |"));
+    }
+    assert!(solution.starts_with("Optimal z is: "));
+}